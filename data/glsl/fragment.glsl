@@ -1,9 +1,10 @@
 #version 330
 in vec2 f_texcoord;
+in vec4 f_color;
 out vec4 color;
 uniform sampler2D tex;
 uniform vec4 color_multiply;
 void main()
 {
-    color = texture(tex, f_texcoord) * color_multiply;
+    color = texture(tex, f_texcoord) * color_multiply * f_color;
 }
@@ -0,0 +1,29 @@
+#version 330
+in vec2 f_texcoord;
+in vec4 f_color;
+flat in vec4 f_tex_rect;
+flat in float f_radius;
+out vec4 color;
+uniform sampler2D tex;
+// grows the glyph by taking the most opaque texel within the radius, which is in texels and capped at 16
+void main()
+{
+    vec2 texel = 1.0/vec2(textureSize(tex, 0));
+    int reach = int(ceil(min(f_radius, 16.0)));
+    float alpha = 0.0;
+    for (int y = -reach; y <= reach; y++)
+    {
+        for (int x = -reach; x <= reach; x++)
+        {
+            vec2 coord = f_texcoord + vec2(x, y)*texel;
+            // the rest of the texture belongs to other glyphs
+            if (any(lessThan(coord, f_tex_rect.xy)) || any(greaterThan(coord, f_tex_rect.zw)))
+            {
+                continue;
+            }
+            float edge = clamp(min(f_radius, 16.0) + 1.0 - length(vec2(x, y)), 0.0, 1.0);
+            alpha = max(alpha, texture(tex, coord).a*edge);
+        }
+    }
+    color = vec4(f_color.rgb, f_color.a*alpha);
+}
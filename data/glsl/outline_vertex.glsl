@@ -0,0 +1,19 @@
+#version 330
+in vec2 position;
+in vec4 vertex_color;
+in vec2 vertex_texcoord;
+in vec4 tex_rect;
+in float radius;
+out vec2 f_texcoord;
+out vec4 f_color;
+flat out vec4 f_tex_rect;
+flat out float f_radius;
+uniform mat4 matrix_view;
+void main()
+{
+    f_texcoord = vertex_texcoord;
+    f_color = vertex_color;
+    f_tex_rect = tex_rect;
+    f_radius = radius;
+    gl_Position = matrix_view * vec4(position, 0.0, 1.0);
+}
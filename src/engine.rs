@@ -2,46 +2,128 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 
+use glium::backend::Facade;
+
 use crate::input::InputHandler;
-use crate::{open_file, load_string};
+use crate::{open_file, load_string, output_path, find_file};
 
 pub (crate) mod bindings;
 pub (crate) mod render;
+pub (crate) mod camera;
+pub (crate) mod spritesheet;
+pub (crate) mod animation;
+pub (crate) mod scaling;
+pub (crate) mod hotreload;
+pub (crate) mod shaderlog;
+pub (crate) mod markup;
+pub (crate) mod texteffects;
+pub (crate) mod bitmapfont;
+pub (crate) mod fallback;
+pub (crate) mod linebreak;
+pub (crate) mod textbatch;
+pub (crate) mod resources;
 
 use self::render::*;
+use self::camera::*;
+use self::animation::*;
+use self::scaling::*;
+use self::hotreload::*;
+use self::shaderlog::*;
+use self::markup::*;
+use self::texteffects::*;
+use self::bitmapfont::*;
+use self::fallback::*;
+use self::linebreak::*;
+use self::textbatch::*;
+use self::resources::*;
+
+// windowed engines present to a window; headless engines present to a surface that gets read back at the end of the run
+pub (crate) enum Backend {
+    Windowed(glium::Display),
+    Headless(glium::HeadlessRenderer, (u32, u32)),
+}
+
+impl glium::backend::Facade for Backend {
+    fn get_context(&self) -> &Rc<glium::backend::Context>
+    {
+        match self
+        {
+            Backend::Windowed(display) => display.get_context(),
+            Backend::Headless(renderer, _) => renderer.get_context(),
+        }
+    }
+}
+
+impl Backend {
+    pub (crate) fn window_display(&self) -> Option<&glium::Display>
+    {
+        match self
+        {
+            Backend::Windowed(display) => Some(display),
+            Backend::Headless(_, _) => None,
+        }
+    }
+}
 
 pub (crate) struct Engine {
     program_path: String,
     prefix: String,
     
-    display: glium::Display,
+    display: Backend,
     
     pub (crate) input_handler: InputHandler,
     
-    sprite_index_counter: u64,
-    sprites: HashMap<u64, SpriteSheet>,
+    sprites: ResourceStore<SpriteSheet>,
+    
+    animations: ResourceStore<AnimationPlayer>,
     
-    program_index_counter : u64,
-    programs: HashMap<u64, Rc<glium::Program>>,
+    programs: ResourceStore<Rc<glium::Program>>,
+    program_errors: Vec<ShaderMessage>,
     
     draw_target: Option<glium::Frame>,
     default_surface: Option<Surface>,
     draw_w: u32,
     draw_h: u32,
+    window_dims: (u32, u32),
+    virtual_resolution: Option<((u32, u32), ScaleMode)>,
+    letterbox_color: [f32; 4],
     matrix_view: [[f32; 4]; 4],
     
-    #[allow(unused)]
-    surface_index_counter : u64,
-    surfaces: HashMap<u64, Surface>,
+    cameras: ResourceStore<Camera>,
+    current_camera: Option<u64>,
+    
+    draw_state: DrawState,
+    clear_color: [f32; 4],
+    auto_clear: bool,
+    
+    surfaces: ResourceStore<Surface>,
     surface_target: Vec<u64>,
+    screen_sampler: SamplerSettings,
+    
+    post_process_index_counter: u64,
+    post_process: Vec<PostProcessPass>,
+    post_process_error: Option<String>,
+    post_targets: Vec<Surface>,
+    // headless engines present frames into this instead of a window
+    presented_frame: Option<Surface>,
+    start_time: std::time::Instant,
     
     vertex_buffer: glium::VertexBuffer<Vertex>,
     indices: glium::index::NoIndices,
+    // reused by every primitive draw, and only reallocated when a primitive needs more vertices than it holds
+    primitive_buffer: glium::VertexBuffer<Vertex>,
+    // bitmap font outlines and shadows; truetype text has its own buffers in the text system
+    outline_buffer: glium::VertexBuffer<OutlineVertex>,
+    outline_program: glium::Program,
+    white_texture: glium::texture::SrgbTexture2d,
     current_program: Rc<glium::Program>,
     
     default_program: Rc<glium::Program>,
     
     text_system: RefCell<TextSystem>,
+    // bitmap fonts share handles with the text system's fonts, and are drawn from their sprite
+    // the flag is set for sprites that were loaded along with the font, which get freed with it
+    bitmap_fonts: HashMap<u64, (u64, BitmapFont, bool)>,
     
     target_frametime: f64, // seconds
     framelimiter_reset_reference_time: Option<std::time::Instant>,
@@ -50,6 +132,9 @@ pub (crate) struct Engine {
     framelimiter_delta: f64,
     framelimiter_check_desync: bool,
     recent_deltas: Vec<f64>,
+    frame_time: f64, // every frame's delta added up; text effects use it instead of the wall clock, so that headless runs draw the same frames every time
+    
+    file_watcher: FileWatcher,
 }
 
 fn duration_to_secs(duration : &std::time::Duration) -> f64
@@ -62,10 +147,15 @@ fn duration_from_secs(secs : f64) -> std::time::Duration
 }
 
 impl Engine {
-    pub (crate) fn load(display : glium::Display, program_path : String, prefix : String) -> Engine
+    pub (crate) fn load(display : Backend, program_path : String, prefix : String) -> Engine
     {
-        let glprogram = Rc::new(Engine::build_glprogram(&display, &program_path, &prefix));
+        let (glprogram, program_errors) = Engine::build_glprogram(&display, &program_path, &prefix);
+        let glprogram = Rc::new(glprogram);
         let (vertex_buffer, indices) = Engine::build_vertex_buffer(&display);
+        let primitive_buffer = glium::VertexBuffer::empty_dynamic(&display, 64).unwrap();
+        let outline_buffer = glium::VertexBuffer::empty_dynamic(&display, 64).unwrap();
+        let outline_program = Engine::build_outline_program(&display);
+        let white_texture = Engine::build_white_texture(&display);
         let text_system = TextSystem::new(&display);
         Engine {
             program_path,
@@ -75,29 +165,52 @@ impl Engine {
             
             input_handler : InputHandler::new(),
             
-            sprite_index_counter : 1,
-            sprites : HashMap::new(),
+            sprites : ResourceStore::new(),
+            
+            animations : ResourceStore::new(),
             
-            program_index_counter : 1,
-            programs : HashMap::new(),
+            programs : ResourceStore::new(),
+            program_errors,
             
             draw_target : None,
             default_surface : None,
             draw_w : 0,
             draw_h : 0,
+            window_dims : (0, 0),
+            virtual_resolution : None,
+            letterbox_color : [0.0, 0.0, 0.0, 1.0],
             matrix_view : [[0.0; 4]; 4],
             
-            surface_index_counter : 1,
-            surfaces : HashMap::new(),
+            cameras : ResourceStore::new(),
+            current_camera : None,
+            
+            draw_state : DrawState::new(),
+            clear_color : [0.5, 0.5, 0.5, 1.0],
+            auto_clear : true,
+            
+            surfaces : ResourceStore::new(),
             surface_target : Vec::new(),
+            screen_sampler : SamplerSettings::new(),
+            
+            post_process_index_counter : 1,
+            post_process : Vec::new(),
+            post_process_error : None,
+            post_targets : Vec::new(),
+            presented_frame : None,
+            start_time : std::time::Instant::now(),
             
             vertex_buffer,
             indices,
+            primitive_buffer,
+            outline_buffer,
+            outline_program,
+            white_texture,
             current_program : Rc::clone(&glprogram),
             
             default_program : Rc::clone(&glprogram),
             
             text_system : RefCell::new(text_system),
+            bitmap_fonts : HashMap::new(),
             
             target_frametime : 0.008,
             framelimiter_reset_reference_time : None,
@@ -106,6 +219,9 @@ impl Engine {
             framelimiter_delta: 0.0,
             framelimiter_check_desync: false,
             recent_deltas: Vec::new(),
+            frame_time: 0.0,
+            
+            file_watcher: FileWatcher::new(),
         }
     }
     pub (crate) fn unsafe_check_global_cursor_position(&mut self)
@@ -122,13 +238,13 @@ impl Engine {
                 cursor_x = cursor_pos.x;
                 cursor_y = cursor_pos.y;
             }
-            if valid != 0
+            if let (true, Some(display)) = (valid != 0, self.display.window_display())
             {
-                let factor = self.display.gl_window().window().get_hidpi_factor();
-                let offset = self.display.gl_window().window().get_inner_position().unwrap();
+                let factor = display.gl_window().window().get_hidpi_factor();
+                let offset = display.gl_window().window().get_inner_position().unwrap();
                 let new_x = factor*cursor_x as f64 - offset.x;
                 let new_y = factor*cursor_y as f64 - offset.y;
-                self.input_handler.mouse_pos = (new_x, new_y);
+                self.input_handler.set_mouse_pos((new_x, new_y));
             }
         }
     }
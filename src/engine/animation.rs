@@ -0,0 +1,262 @@
+use super::render::{SpriteImage, SpriteTag};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub (super) enum LoopMode {
+    Loop,
+    Once,
+    PingPong,
+}
+
+impl LoopMode {
+    pub (super) fn from_name(name : &str) -> Result<LoopMode, String>
+    {
+        match name
+        {
+            "loop" => Ok(LoopMode::Loop),
+            "once" => Ok(LoopMode::Once),
+            "pingpong" => Ok(LoopMode::PingPong),
+            _ => Err(format!("error: unknown animation loop mode `{}`; expected loop, once, or pingpong", name))
+        }
+    }
+}
+
+// a sequence of sprite images, each shown for its own duration in seconds
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct Animation {
+    pub (super) frames: Vec<usize>,
+    pub (super) durations: Vec<f64>,
+    pub (super) mode: LoopMode,
+}
+
+impl Animation {
+    pub (super) fn new(frames : Vec<usize>, durations : Vec<f64>, mode : LoopMode) -> Result<Animation, String>
+    {
+        if frames.is_empty()
+        {
+            return Err("error: animations must have at least one frame".to_string());
+        }
+        if frames.len() != durations.len()
+        {
+            return Err(format!("error: animation has {} frames but {} durations", frames.len(), durations.len()));
+        }
+        if durations.iter().any(|duration| duration.is_nan() || *duration <= 0.0)
+        {
+            return Err("error: animation frame durations must be greater than zero".to_string());
+        }
+        Ok(Animation{frames, durations, mode})
+    }
+    // aseprite tags play forward, in reverse, or back and forth, and always repeat
+    pub (super) fn from_tag(tag : &SpriteTag, images : &[SpriteImage], default_duration : f64) -> Result<Animation, String>
+    {
+        let mut frames : Vec<usize> = (tag.from..=tag.to).collect();
+        let mode = match tag.direction.as_str()
+        {
+            "forward" => LoopMode::Loop,
+            "reverse" =>
+            {
+                frames.reverse();
+                LoopMode::Loop
+            }
+            "pingpong" => LoopMode::PingPong,
+            _ => return Err(format!("error: sprite tag `{}` has unknown direction `{}`", tag.name, tag.direction))
+        };
+        let durations = frames.iter().map(|frame| images.get(*frame).and_then(|image| image.duration).unwrap_or(default_duration)).collect();
+        Animation::new(frames, durations, mode)
+    }
+    // the order that frames are visited in over one loop; ping-pong doesn't repeat its first and last frames when it turns around
+    fn sequence_len(&self) -> usize
+    {
+        match self.mode
+        {
+            LoopMode::PingPong if self.frames.len() > 2 => self.frames.len()*2 - 2,
+            _ => self.frames.len()
+        }
+    }
+    fn step_duration(&self, step : usize) -> f64
+    {
+        self.durations[self.sequence_frame(step)]
+    }
+    // how long it takes to get through the first `steps` steps of the sequence
+    fn sequence_duration(&self, steps : usize) -> f64
+    {
+        (0..steps).map(|step| self.step_duration(step)).sum()
+    }
+    fn sequence_frame(&self, step : usize) -> usize
+    {
+        if step < self.frames.len()
+        {
+            step
+        }
+        else
+        {
+            self.frames.len()*2 - 2 - step
+        }
+    }
+}
+
+// playback state for one animation; scripts poll it for the current image and for whether it has ended
+#[derive(Debug, Clone)]
+pub (super) struct AnimationPlayer {
+    pub (super) sprite: u64,
+    pub (super) name: String,
+    pub (super) speed: f64,
+    pub (super) paused: bool,
+    animation: Animation,
+    step: usize,
+    time: f64,
+    loops: u64,
+    finished: bool,
+}
+
+impl AnimationPlayer {
+    pub (super) fn new(sprite : u64, name : &str, animation : Animation) -> AnimationPlayer
+    {
+        AnimationPlayer{sprite, name : name.to_string(), speed : 1.0, paused : false, animation, step : 0, time : 0.0, loops : 0, finished : false}
+    }
+    pub (super) fn play(&mut self, name : &str, animation : Animation)
+    {
+        self.name = name.to_string();
+        self.animation = animation;
+        self.restart();
+    }
+    pub (super) fn restart(&mut self)
+    {
+        self.step = 0;
+        self.time = 0.0;
+        self.loops = 0;
+        self.finished = false;
+    }
+    pub (super) fn advance(&mut self, delta : f64)
+    {
+        if self.paused || self.finished
+        {
+            return;
+        }
+        let len = self.animation.sequence_len();
+        let total = self.animation.sequence_duration(len);
+        // measured from the start of the current loop, so that big steps wrap around at once instead of walking every frame
+        let mut time = self.animation.sequence_duration(self.step) + self.time + delta*self.speed.max(0.0);
+        if time >= total || time.is_nan()
+        {
+            if self.animation.mode == LoopMode::Once
+            {
+                self.step = len - 1;
+                self.time = 0.0;
+                self.finished = true;
+                return;
+            }
+            self.loops = self.loops.saturating_add((time/total) as u64);
+            time %= total;
+            if !time.is_finite()
+            {
+                time = 0.0;
+            }
+        }
+        self.step = 0;
+        while self.step + 1 < len && time >= self.animation.step_duration(self.step)
+        {
+            time -= self.animation.step_duration(self.step);
+            self.step += 1;
+        }
+        self.time = time;
+    }
+    pub (super) fn image(&self) -> usize
+    {
+        self.animation.frames[self.animation.sequence_frame(self.step)]
+    }
+    // index into the animation's own frame list, rather than into the sprite's images
+    pub (super) fn frame(&self) -> usize
+    {
+        self.animation.sequence_frame(self.step)
+    }
+    pub (super) fn finished(&self) -> bool
+    {
+        self.finished
+    }
+    pub (super) fn loops(&self) -> u64
+    {
+        self.loops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_loop_with_durations()
+    {
+        let animation = Animation::new(vec!(4, 5, 6), vec!(0.1, 0.3, 0.1), LoopMode::Loop).unwrap();
+        let mut player = AnimationPlayer::new(1, "walk", animation);
+        assert_eq!(player.image(), 4);
+        player.advance(0.15);
+        assert_eq!(player.image(), 5);
+        player.advance(0.3);
+        assert_eq!(player.image(), 6);
+        player.advance(0.1);
+        assert_eq!(player.image(), 4);
+        assert_eq!(player.loops(), 1);
+        assert!(!player.finished());
+    }
+    
+    #[test]
+    fn test_once_stops_on_last_frame()
+    {
+        let animation = Animation::new(vec!(0, 1, 2), vec!(0.1; 3), LoopMode::Once).unwrap();
+        let mut player = AnimationPlayer::new(1, "attack", animation);
+        player.advance(10.0);
+        assert_eq!(player.image(), 2);
+        assert!(player.finished());
+        player.restart();
+        assert_eq!(player.image(), 0);
+        assert!(!player.finished());
+    }
+    
+    #[test]
+    fn test_pingpong()
+    {
+        let animation = Animation::new(vec!(0, 1, 2), vec!(1.0; 3), LoopMode::PingPong).unwrap();
+        let mut player = AnimationPlayer::new(1, "bob", animation);
+        let mut seen = Vec::new();
+        for _ in 0..6
+        {
+            seen.push(player.image());
+            player.advance(1.0);
+        }
+        assert_eq!(seen, vec!(0, 1, 2, 1, 0, 1));
+        assert_eq!(player.loops(), 1);
+    }
+    
+    #[test]
+    fn test_large_steps_wrap_at_once()
+    {
+        let animation = Animation::new(vec!(0, 1, 2), vec!(0.001; 3), LoopMode::Loop).unwrap();
+        let mut player = AnimationPlayer::new(1, "spin", animation.clone());
+        player.advance(3000.0015);
+        assert_eq!(player.image(), 1);
+        assert_eq!(player.loops(), 1_000_000);
+        player.speed = f64::INFINITY;
+        player.advance(0.1);
+        assert_eq!(player.image(), 0);
+        assert!(!player.finished());
+        let mut player = AnimationPlayer::new(1, "spin", Animation{mode : LoopMode::Once, ..animation});
+        player.speed = f64::INFINITY;
+        player.advance(0.1);
+        assert_eq!(player.image(), 2);
+        assert!(player.finished());
+    }
+    
+    #[test]
+    fn test_from_tag()
+    {
+        let images = vec!(
+            SpriteImage::extended((0.0, 0.0), (0.0, 0.0), (8.0, 8.0)).with_duration(Some(0.2)),
+            SpriteImage::extended((0.0, 0.0), (8.0, 0.0), (16.0, 8.0)),
+        );
+        let tag = SpriteTag{name : "spin".to_string(), from : 0, to : 1, direction : "reverse".to_string()};
+        let animation = Animation::from_tag(&tag, &images, 0.05).unwrap();
+        assert_eq!(animation.frames, vec!(1, 0));
+        assert_eq!(animation.durations, vec!(0.05, 0.2));
+        assert!(Animation::new(vec!(0), vec!(0.0), LoopMode::Loop).is_err());
+    }
+}
@@ -43,191 +43,1944 @@ fn default_return() -> Result<Value, String>
     Ok(Value::Number(0.0 as f64))
 }
 
+// colors are arrays of three or four numbers from 0 to 1: [r, g, b] or [r, g, b, a]
+fn pop_color(args : &mut Vec<Value>) -> Result<[f32; 4], String>
+{
+    let mut color = pop_front!(args, Array)?;
+    if color.len() != 3 && color.len() != 4
+    {
+        return Err("error: colors must be arrays of 3 or 4 numbers".to_string());
+    }
+    let r = pop_front!(color, Number)? as f32;
+    let g = pop_front!(color, Number)? as f32;
+    let b = pop_front!(color, Number)? as f32;
+    let a = if color.is_empty() { 1.0 } else { pop_front!(color, Number)? as f32 };
+    Ok([r, g, b, a])
+}
+fn pop_point(args : &mut Vec<Value>) -> Result<(f32, f32), String>
+{
+    let mut point = pop_front!(args, Array)?;
+    if point.len() != 2
+    {
+        return Err("error: points must be arrays of 2 numbers".to_string());
+    }
+    let x = pop_front!(point, Number)? as f32;
+    let y = pop_front!(point, Number)? as f32;
+    Ok((x, y))
+}
+// text options are an optional array of [name, value] pairs, like [["wrap", "char"], ["max_lines", 3], ["ellipsis", 1]]
+fn pop_text_options(args : &mut Vec<Value>) -> Result<TextOptions, String>
+{
+    let mut options = TextOptions::new();
+    if args.is_empty()
+    {
+        return Ok(options);
+    }
+    let mut list = pop_front!(args, Array)?;
+    while !list.is_empty()
+    {
+        let mut pair = pop_front!(list, Array)?;
+        if pair.len() != 2
+        {
+            return Err("error: text options must be arrays of a name and a value".to_string());
+        }
+        let name = pop_front!(pair, Text)?;
+        match name.as_str()
+        {
+            "wrap" => options.wrap = text_wrap_from_name(&pop_front!(pair, Text)?)?,
+            "line_spacing" => options.line_spacing = pop_front!(pair, Number)?.max(0.0) as f32,
+            "max_lines" => options.max_lines = Some(pop_front!(pair, Number)?).filter(|count| *count >= 1.0).map(|count| count as usize),
+            "ellipsis" => options.ellipsis = pop_front!(pair, Number)? != 0.0,
+            "direction" => options.direction = text_direction_from_name(&pop_front!(pair, Text)?)?,
+            _ => return Err(format!("error: unknown text option `{}`; expected wrap, line_spacing, max_lines, ellipsis, or direction", name))
+        }
+    }
+    Ok(options)
+}
+
+fn pixels_to_array(pixels : Vec<u8>) -> Value
+{
+    Value::Array(Box::new(pixels.into_iter().map(|x| Value::Number(x as f64)).collect()))
+}
+
 impl Engine {
     fn binding_program_load(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
-        if args.len() != 2
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to program_load()".to_string());
+        }
+        let filename_vertex = pop_front!(args, Text)?;
+        let filename_fragment = pop_front!(args, Text)?;
+        
+        Ok(build_custom(1, self.load_program(&filename_vertex, &filename_fragment)?))
+    }
+    // like program_load, but gives 0 instead of stopping the script when the program fails to compile; see program_get_errors
+    fn binding_program_try_load(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to program_try_load()".to_string());
+        }
+        let filename_vertex = pop_front!(args, Text)?;
+        let filename_fragment = pop_front!(args, Text)?;
+        
+        match self.load_program(&filename_vertex, &filename_fragment)
+        {
+            Ok(index) => Ok(build_custom(1, index)),
+            Err(_) => default_return()
+        }
+    }
+    // errors from the last attempt to load or reload a program, as [file, line, message] arrays; line is -1 when unknown
+    // that includes the game's default shaders failing at startup; a successful load clears them, so this is empty unless the latest attempt failed
+    fn binding_program_get_errors(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to program_get_errors()".to_string());
+        }
+        let errors = self.program_errors.iter().map(|error|
+        {
+            let line = error.line.map(|line| line as f64).unwrap_or(-1.0);
+            Value::Array(Box::new(vec!(Value::Text(error.file.clone()), Value::Number(line), Value::Text(error.message.clone()))))
+        }).collect();
+        Ok(Value::Array(Box::new(errors)))
+    }
+    fn binding_program_set(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to program_set()".to_string());
+        }
+        let program_index_wrapped = pop_front!(args, Custom)?;
+        let program_index = match_custom(program_index_wrapped, 1)?;
+        
+        self.set_program(program_index)?;
+        default_return()
+    }
+    fn binding_program_reset(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to program_set()".to_string());
+        }
+        self.reset_program();
+        default_return()
+    }
+    // the current program goes back to the default one if it's the one being freed
+    fn binding_program_free(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to program_free()".to_string());
+        }
+        let program_index = match_custom(pop_front!(args, Custom)?, 1)?;
+        self.free_program(program_index)?;
+        default_return()
+    }
+    // post-process passes run in the order they were pushed, each one reading the output of the one before it
+    // returns an id for the pass; passes that failed to draw last frame are reported as errors by the next postprocess_ call
+    fn binding_postprocess_push(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to postprocess_push()".to_string());
+        }
+        self.check_post_process_error()?;
+        let program_index = match_custom(pop_front!(args, Custom)?, 1)?;
+        Ok(Value::Number(self.push_post_process(program_index)? as f64))
+    }
+    // values can be numbers, arrays of 2 to 4 numbers, sprites, or surfaces
+    fn binding_postprocess_set_uniform(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to postprocess_set_uniform()".to_string());
+        }
+        self.check_post_process_error()?;
+        let pass_id = pop_front!(args, Number)?.max(0.0) as u64;
+        let name = pop_front!(args, Text)?;
+        let value = match args.remove(0)
+        {
+            Value::Number(x) => ShaderValue::Float(x as f32),
+            Value::Array(list) =>
+            {
+                let list = list.into_iter().map(|x| match x
+                {
+                    Value::Number(x) => Ok(x as f32),
+                    _ => Err("error: uniform arrays must only contain numbers".to_string())
+                }).collect::<Result<Vec<f32>, String>>()?;
+                match list.len()
+                {
+                    2 => ShaderValue::Vec2([list[0], list[1]]),
+                    3 => ShaderValue::Vec3([list[0], list[1], list[2]]),
+                    4 => ShaderValue::Vec4([list[0], list[1], list[2], list[3]]),
+                    _ => return Err("error: uniform arrays must have 2, 3, or 4 numbers".to_string())
+                }
+            }
+            Value::Custom(custom) => match custom.discrim
+            {
+                0 => ShaderValue::Sprite(custom.storage),
+                4 => ShaderValue::Surface(custom.storage),
+                _ => return Err("error: only sprites and surfaces can be given to shaders as textures".to_string())
+            }
+            _ => return Err("error: given value had wrong type in binding".to_string())
+        };
+        self.get_post_process_mut(pass_id)?.set_uniform(&name, value);
+        default_return()
+    }
+    fn binding_postprocess_remove(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to postprocess_remove()".to_string());
+        }
+        self.check_post_process_error()?;
+        let pass_id = pop_front!(args, Number)?.max(0.0) as u64;
+        self.remove_post_process(pass_id)?;
+        default_return()
+    }
+    fn binding_postprocess_clear(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to postprocess_clear()".to_string());
+        }
+        self.check_post_process_error()?;
+        self.post_process.clear();
+        default_return()
+    }
+    fn binding_postprocess_get_count(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to postprocess_get_count()".to_string());
+        }
+        self.check_post_process_error()?;
+        Ok(Value::Number(self.post_process.len() as f64))
+    }
+    // resources loaded from files are reloaded in place when their files change, checking every `interval` seconds
+    fn binding_hotreload_enable(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to hotreload_enable()".to_string());
+        }
+        let enabled = pop_front!(args, Number)? != 0.0;
+        let interval = pop_front!(args, Number)?;
+        self.file_watcher.enabled = enabled;
+        self.file_watcher.interval = interval.max(0.0);
+        default_return()
+    }
+    // reloads since the last call, as [kind, file, error] arrays; kind is sprite, program, or font, and error is "" when the reload worked
+    fn binding_hotreload_get_events(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to hotreload_get_events()".to_string());
+        }
+        let events = self.file_watcher.take_events().into_iter().map(|event|
+        {
+            let error = event.error.unwrap_or_default();
+            Value::Array(Box::new(vec!(Value::Text(event.kind.to_string()), Value::Text(event.files), Value::Text(error))))
+        }).collect();
+        Ok(Value::Array(Box::new(events)))
+    }
+    fn binding_file_load_to_string(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 arguments to file_load_to_string()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        
+        let string = load_string(&self.program_path, &self.prefix, &filename)?;
+        Ok(Value::Text(string))
+    }
+    fn binding_draw_text(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_text()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let (size, color) = (self.draw_state.text_size, self.draw_state.color);
+        self.draw_text(&text, x, y, 999999999.0, 999999999.0, size, color)?;
+        
+        default_return()
+    }
+    // the optional last argument is a list of text options: wrap ("word", "char", or "none"), line_spacing, max_lines, ellipsis, and direction ("auto", "ltr", or "rtl")
+    fn binding_draw_text_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 6 && args.len() != 7
+        {
+            return Err("error: expected 6 or 7 arguments to draw_text_ext()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let w = pop_front!(args, Number)? as f32;
+        let h = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let options = pop_text_options(&mut args)?;
+        let color = self.draw_state.color;
+        self.draw_text_ext(&text, x, y, w, h, size, color, &options)?;
+        
+        default_return()
+    }
+    fn binding_draw_text_color(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 4
+        {
+            return Err("error: expected exactly 4 arguments to draw_text_color()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let color = pop_color(&mut args)?;
+        let size = self.draw_state.text_size;
+        self.draw_text(&text, x, y, 999999999.0, 999999999.0, size, color)?;
+        
+        default_return()
+    }
+    fn binding_draw_text_ext_color(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 7 && args.len() != 8
+        {
+            return Err("error: expected 7 or 8 arguments to draw_text_ext_color()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let w = pop_front!(args, Number)? as f32;
+        let h = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let color = pop_color(&mut args)?;
+        let options = pop_text_options(&mut args)?;
+        self.draw_text_ext(&text, x, y, w, h, size, color, &options)?;
+        
+        default_return()
+    }
+    // text with markup like [b], [i], [color=#f00], [size=32], and [font=Mono], drawn with the current color and text size
+    fn binding_draw_text_rich(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_text_rich()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let (size, color) = (self.draw_state.text_size, self.draw_state.color);
+        self.draw_text_rich(&text, x, y, 999999999.0, 999999999.0, size, color, std::f32::INFINITY)?;
+        
+        default_return()
+    }
+    fn binding_draw_text_rich_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 6
+        {
+            return Err("error: expected exactly 6 arguments to draw_text_rich_ext()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let w = pop_front!(args, Number)? as f32;
+        let h = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let color = self.draw_state.color;
+        self.draw_text_rich(&text, x, y, w, h, size, color, std::f32::INFINITY)?;
+        
+        default_return()
+    }
+    // like draw_text_rich_ext, but only the first `count` glyphs are shown; a fractional count fades in the last one
+    fn binding_draw_text_reveal(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 7
+        {
+            return Err("error: expected exactly 7 arguments to draw_text_reveal()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let w = pop_front!(args, Number)? as f32;
+        let h = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let count = pop_front!(args, Number)? as f32;
+        let color = self.draw_state.color;
+        self.draw_text_rich(&text, x, y, w, h, size, color, count)?;
+        
+        default_return()
+    }
+    // reveals glyphs at a fixed rate, from how long the text has been shown for
+    fn binding_draw_text_reveal_time(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 8
+        {
+            return Err("error: expected exactly 8 arguments to draw_text_reveal_time()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let w = pop_front!(args, Number)? as f32;
+        let h = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let time = pop_front!(args, Number)?;
+        let glyphs_per_second = pop_front!(args, Number)?;
+        let color = self.draw_state.color;
+        self.draw_text_rich(&text, x, y, w, h, size, color, (time*glyphs_per_second) as f32)?;
+        
+        default_return()
+    }
+    // how far [wave] text moves, how quickly the wave travels along it, and how fast it moves, in radians per glyph and per second
+    fn binding_draw_set_text_wave(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_set_text_wave()".to_string());
+        }
+        let effects = &mut self.draw_state.text_effects;
+        effects.wave_amplitude = pop_front!(args, Number)? as f32;
+        effects.wave_frequency = pop_front!(args, Number)? as f32;
+        effects.wave_speed = pop_front!(args, Number)? as f32;
+        default_return()
+    }
+    // an outline of the given width in pixels around all text drawn after this; a width of 0 turns it off
+    // outlines reach at most 16 texels past each glyph, which is 16 pixels for truetype fonts and scales with bitmap fonts
+    fn binding_draw_set_text_outline(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to draw_set_text_outline()".to_string());
+        }
+        let width = pop_front!(args, Number)? as f32;
+        let color = pop_color(&mut args)?;
+        self.draw_state.text_decoration.outline = if width > 0.0 { Some((width, color)) } else { None };
+        default_return()
+    }
+    fn binding_draw_set_text_shadow(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_set_text_shadow()".to_string());
+        }
+        let xoffset = pop_front!(args, Number)? as f32;
+        let yoffset = pop_front!(args, Number)? as f32;
+        let color = pop_color(&mut args)?;
+        self.draw_state.text_decoration.shadow = Some(((xoffset, yoffset), color));
+        default_return()
+    }
+    fn binding_draw_reset_text_shadow(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to draw_reset_text_shadow()".to_string());
+        }
+        self.draw_state.text_decoration.shadow = None;
+        default_return()
+    }
+    // how far [shake] text moves, and how many times per second it picks new offsets
+    fn binding_draw_set_text_shake(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to draw_set_text_shake()".to_string());
+        }
+        let effects = &mut self.draw_state.text_effects;
+        effects.shake_amplitude = pop_front!(args, Number)? as f32;
+        effects.shake_rate = pop_front!(args, Number)?.max(0.0) as f32;
+        default_return()
+    }
+    // the color also sets the alpha, which is 1 if the color only has three components
+    fn binding_draw_set_color(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_color()".to_string());
+        }
+        self.draw_state.color = pop_color(&mut args)?;
+        default_return()
+    }
+    fn binding_draw_set_alpha(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_alpha()".to_string());
+        }
+        self.draw_state.color[3] = pop_front!(args, Number)? as f32;
+        default_return()
+    }
+    fn binding_draw_get_color(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to draw_get_color()".to_string());
+        }
+        Ok(Value::Array(Box::new(self.draw_state.color.iter().map(|x| Value::Number(*x as f64)).collect())))
+    }
+    fn binding_draw_set_halign(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_halign()".to_string());
+        }
+        self.draw_state.halign = halign_from_name(&pop_front!(args, Text)?)?;
+        default_return()
+    }
+    fn binding_draw_set_valign(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_valign()".to_string());
+        }
+        self.draw_state.valign = valign_from_name(&pop_front!(args, Text)?)?;
+        default_return()
+    }
+    // the size used by draw_text and draw_text_color
+    fn binding_draw_set_text_size(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_text_size()".to_string());
+        }
+        self.draw_state.text_size = pop_front!(args, Number)?.max(0.0) as f32;
+        default_return()
+    }
+    // text without wrapping, at the size set by draw_set_text_size
+    fn binding_string_width(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to string_width()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        Ok(Value::Number(self.measure_text(&text, 999999999.0, self.draw_state.text_size).0 as f64))
+    }
+    fn binding_string_height(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to string_height()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        Ok(Value::Number(self.measure_text(&text, 999999999.0, self.draw_state.text_size).1 as f64))
+    }
+    // the number of glyphs that draw_text_reveal counts through with the current font, given the same box and size; markup tags,
+    // chars that the font draws nothing for (like spaces), and glyphs that fall outside of the box aren't counted
+    fn binding_string_glyph_count(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 4
+        {
+            return Err("error: expected exactly 4 arguments to string_glyph_count()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let w = pop_front!(args, Number)? as f32;
+        let h = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        Ok(Value::Number(self.rich_glyph_count(&text, w, h, size)? as f64))
+    }
+    // text wrapped to the given width, like draw_text_ext, with the same optional text options
+    fn binding_string_width_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3 && args.len() != 4
+        {
+            return Err("error: expected 3 or 4 arguments to string_width_ext()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let w = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let options = pop_text_options(&mut args)?;
+        Ok(Value::Number(self.measure_text_ext(&text, w, size, &options).0 as f64))
+    }
+    fn binding_string_height_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3 && args.len() != 4
+        {
+            return Err("error: expected 3 or 4 arguments to string_height_ext()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let w = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let options = pop_text_options(&mut args)?;
+        Ok(Value::Number(self.measure_text_ext(&text, w, size, &options).1 as f64))
+    }
+    // returns an array of [left, top, width, height] for each line, relative to where the text would be drawn
+    fn binding_string_get_lines(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3 && args.len() != 4
+        {
+            return Err("error: expected 3 or 4 arguments to string_get_lines()".to_string());
+        }
+        let text = pop_front!(args, Text)?;
+        let w = pop_front!(args, Number)? as f32;
+        let size = pop_front!(args, Number)? as f32;
+        let options = pop_text_options(&mut args)?;
+        let lines = self.measure_text_lines(&text, w, size, &options).into_iter().map(|(left, top, width, height)|
+        {
+            Value::Array(Box::new(vec!(Value::Number(left as f64), Value::Number(top as f64), Value::Number(width as f64), Value::Number(height as f64))))
+        }).collect();
+        Ok(Value::Array(Box::new(lines)))
+    }
+    // returns [ascent, descent, line_gap] of the current font; descent is negative
+    fn binding_font_get_metrics(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to font_get_metrics()".to_string());
+        }
+        let size = pop_front!(args, Number)? as f32;
+        let (ascent, descent, line_gap) = self.font_metrics(size);
+        Ok(Value::Array(Box::new(vec!(Value::Number(ascent as f64), Value::Number(descent as f64), Value::Number(line_gap as f64)))))
+    }
+    fn binding_sprite_load(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to sprite_load()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        let xoffset = pop_front!(args, Number)?;
+        let yoffset = pop_front!(args, Number)?;
+        
+        Ok(build_custom(0, self.load_sprite(&filename, (xoffset, yoffset))?))
+    }
+    fn binding_sprite_load_strip(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 5
+        {
+            return Err("error: expected exactly 5 arguments to sprite_load_strip()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        let frame_w = pop_front!(args, Number)?;
+        let frame_h = pop_front!(args, Number)?;
+        let count = pop_front!(args, Number)?.max(0.0) as usize;
+        let (xorigin, yorigin) = pop_point(&mut args)?;
+        
+        Ok(build_custom(0, self.load_sprite_strip(&filename, (frame_w, frame_h), count, (xorigin as f64, yorigin as f64))?))
+    }
+    fn binding_sprite_load_sheet(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to sprite_load_sheet()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        let (xorigin, yorigin) = pop_point(&mut args)?;
+        
+        Ok(build_custom(0, self.load_sprite_sheet(&filename, (xorigin as f64, yorigin as f64))?))
+    }
+    // the sprite's handle, and any other copies of it, give an error from then on instead of drawing
+    fn binding_sprite_free(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to sprite_free()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        self.free_sprite(sprite_index)?;
+        default_return()
+    }
+    fn binding_sprite_get_image_count(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to sprite_get_image_count()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        Ok(Value::Number(self.get_sprite(sprite_index)?.images().len() as f64))
+    }
+    fn binding_sprite_get_image_duration(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to sprite_get_image_duration()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let image_index = pop_front!(args, Number)?.floor().max(0.0) as usize;
+        let images = self.get_sprite(sprite_index)?.images();
+        Ok(Value::Number(images[image_index % images.len()].duration.unwrap_or(0.0)))
+    }
+    fn binding_sprite_get_tags(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to sprite_get_tags()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let tags = self.get_sprite(sprite_index)?.tags().iter().map(|tag|
+        {
+            Value::Array(Box::new(vec!(Value::Text(tag.name.clone()), Value::Number(tag.from as f64), Value::Number(tag.to as f64), Value::Text(tag.direction.clone()))))
+        }).collect();
+        Ok(Value::Array(Box::new(tags)))
+    }
+    fn binding_sprite_load_from_bytes(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to sprite_load_from_bytes()".to_string());
+        }
+        let mut byte_list = pop_front!(args, Array)?;
+        let xoffset = pop_front!(args, Number)?;
+        let yoffset = pop_front!(args, Number)?;
+        
+        let mut bytes = Vec::with_capacity(byte_list.len());
+        while !byte_list.is_empty()
+        {
+            let byte = pop_front!(byte_list, Number)?;
+            if byte < 0.0 || byte > 255.0 || byte.fract() != 0.0
+            {
+                return Err("error: sprite_load_from_bytes must be given an array of whole numbers from 0 to 255".to_string());
+            }
+            bytes.push(byte as u8);
+        }
+        
+        Ok(build_custom(0, self.load_sprite_from_bytes(&bytes, (xoffset, yoffset))?))
+    }
+    fn binding_sprite_load_with_subimages(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to sprite_load_with_subimages()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        let mut subimages = pop_front!(args, Array)?;
+        
+        let mut subimages_vec = Vec::new();
+        while !subimages.is_empty()
+        {
+            let mut subimage = pop_front!(subimages, Array)?;
+            macro_rules! pop { () => { pop_front!(subimage, Number)? } }
+            subimages_vec.push(SpriteImage::extended((pop!(), pop!()), (pop!(), pop!()), (pop!(), pop!())));
+        }
+        if subimages_vec.is_empty()
+        {
+            return Err("error: sprite_load_with_subimages must be given at least one subimage".to_string());
+        }
+        
+        Ok(build_custom(0, self.load_sprite_with_subimages(&filename, subimages_vec)?))
+    }
+    fn binding_font_load(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 arguments to font_load()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        
+        Ok(build_custom(2, self.load_font(&filename)?))
+    }
+    // either font_load_bitmap(fnt_filename) for a BMFont file, or font_load_bitmap(sprite, chars, spacing) where each image of the sprite is one char
+    fn binding_font_load_bitmap(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        match args.len()
+        {
+            1 =>
+            {
+                let filename = pop_front!(args, Text)?;
+                Ok(build_custom(2, self.load_bitmap_font(&filename)?))
+            }
+            3 =>
+            {
+                let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+                let chars = pop_front!(args, Text)?;
+                let spacing = pop_front!(args, Number)? as f32;
+                Ok(build_custom(2, self.load_bitmap_font_from_sprite(sprite_index, &chars, spacing)?))
+            }
+            _ => Err("error: expected 1 or 3 arguments to font_load_bitmap()".to_string())
+        }
+    }
+    fn binding_font_set(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 arguments to font_set()".to_string());
+        }
+        let font_index_wrapped = pop_front!(args, Custom)?;
+        let font_index = match_custom(font_index_wrapped, 2)?;
+        self.set_font(font_index)?;
+        default_return()
+    }
+    fn binding_font_reset(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 1 arguments to font_reset()".to_string());
+        }
+        self.set_font(0)?;
+        default_return()
+    }
+    // the current font goes back to the built-in one if it's the one being freed; bitmap fonts loaded from .fnt files free their page sprite too
+    fn binding_font_free(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to font_free()".to_string());
+        }
+        let font_index = match_custom(pop_front!(args, Custom)?, 2)?;
+        self.free_font(font_index)?;
+        default_return()
+    }
+    // the built-in font, so that it can be named and given variants like any loaded font
+    fn binding_font_get_default(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to font_get_default()".to_string());
+        }
+        Ok(build_custom(2, 0))
+    }
+    // the name that [font=Name] markup refers to
+    fn binding_font_set_name(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to font_set_name()".to_string());
+        }
+        let font_index = match_custom(pop_front!(args, Custom)?, 2)?;
+        let name = pop_front!(args, Text)?;
+        self.text_system.borrow_mut().set_font_name(font_index, &name)?;
+        default_return()
+    }
+    // the font that [b], [i], or both switch to while this font is in use; style is "bold", "italic", or "bold_italic"
+    fn binding_font_set_variant(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to font_set_variant()".to_string());
+        }
+        let font_index = match_custom(pop_front!(args, Custom)?, 2)?;
+        let style = pop_front!(args, Text)?;
+        let variant_index = match_custom(pop_front!(args, Custom)?, 2)?;
+        let (bold, italic) = match style.as_str()
+        {
+            "bold" => (true, false),
+            "italic" => (false, true),
+            "bold_italic" => (true, true),
+            _ => return Err(format!("error: unknown font variant `{}`; expected bold, italic, or bold_italic", style))
+        };
+        self.text_system.borrow_mut().set_font_variant(font_index, bold, italic, variant_index)?;
+        default_return()
+    }
+    // glyphs that the font doesn't have are drawn from the first of these fonts that does; an empty array removes the fallbacks
+    // bold and italic variants without fallbacks of their own use the fallbacks of the font they're a variant of
+    fn binding_font_set_fallbacks(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to font_set_fallbacks()".to_string());
+        }
+        let font_index = match_custom(pop_front!(args, Custom)?, 2)?;
+        let mut list = pop_front!(args, Array)?;
+        let mut fallbacks = Vec::new();
+        while !list.is_empty()
+        {
+            fallbacks.push(match_custom(pop_front!(list, Custom)?, 2)?);
+        }
+        self.text_system.borrow_mut().set_font_fallbacks(font_index, fallbacks)?;
+        default_return()
+    }
+    fn binding_draw_sprite(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_sprite()".to_string());
+        }
+        let sprite_index_wrapped = pop_front!(args, Custom)?;
+        let sprite_index = match_custom(sprite_index_wrapped, 0)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        self.draw_sprite(sprite_index, 0, x, y)?;
+        
+        default_return()
+    }
+    fn binding_draw_sprite_scaled(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 5
+        {
+            return Err("error: expected exactly 5 arguments to draw_sprite_scaled()".to_string());
+        }
+        let sprite_index_wrapped = pop_front!(args, Custom)?;
+        let sprite_index = match_custom(sprite_index_wrapped, 0)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let xscale = pop_front!(args, Number)? as f32;
+        let yscale = pop_front!(args, Number)? as f32;
+        self.draw_sprite_scaled(sprite_index, 0, x, y, xscale, yscale)?;
+        
+        default_return()
+    }
+    fn binding_draw_sprite_index(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 4
+        {
+            return Err("error: expected exactly 4 arguments to draw_sprite_index()".to_string());
+        }
+        let sprite_index_wrapped = pop_front!(args, Custom)?;
+        let sprite_index = match_custom(sprite_index_wrapped, 0)?;
+        
+        let image_index = pop_front!(args, Number)?.floor() as u64;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        
+        self.draw_sprite(sprite_index, image_index, x, y)?;
+        
+        default_return()
+    }
+    fn binding_draw_sprite_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 9
+        {
+            return Err("error: expected exactly 9 arguments to draw_sprite_ext()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let image_index = pop_front!(args, Number)?.floor() as u64;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let xscale = pop_front!(args, Number)? as f32;
+        let yscale = pop_front!(args, Number)? as f32;
+        let angle = pop_front!(args, Number)? as f32;
+        let mut color = pop_color(&mut args)?;
+        color[3] *= pop_front!(args, Number)? as f32;
+        
+        self.draw_sprite_angled(sprite_index, image_index, x, y, xscale, yscale, angle, color)?;
+        
+        default_return()
+    }
+    // the matrix is an array of four columns of four numbers each, applied to the sprite after its origin is moved to (0, 0)
+    fn binding_draw_sprite_matrix(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 4
+        {
+            return Err("error: expected exactly 4 arguments to draw_sprite_matrix()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let image_index = pop_front!(args, Number)?.floor() as u64;
+        let mut columns = pop_front!(args, Array)?;
+        let color = pop_color(&mut args)?;
+        
+        if columns.len() != 4
+        {
+            return Err("error: matrix given to draw_sprite_matrix() must be an array of 4 columns".to_string());
+        }
+        let mut matrix = [[0.0f32; 4]; 4];
+        for column in matrix.iter_mut()
+        {
+            let mut values = pop_front!(columns, Array)?;
+            if values.len() != 4
+            {
+                return Err("error: matrix given to draw_sprite_matrix() must have 4 numbers in each column".to_string());
+            }
+            for value in column.iter_mut()
+            {
+                *value = pop_front!(values, Number)? as f32;
+            }
+        }
+        
+        self.draw_sprite_transformed(sprite_index, image_index, matrix, color)?;
+        
+        default_return()
+    }
+    fn binding_draw_sprite_part(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 8
+        {
+            return Err("error: expected exactly 8 arguments to draw_sprite_part()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let image_index = pop_front!(args, Number)?.floor() as u64;
+        let left = pop_front!(args, Number)?;
+        let top = pop_front!(args, Number)?;
+        let width = pop_front!(args, Number)?;
+        let height = pop_front!(args, Number)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        
+        self.draw_sprite_part(sprite_index, image_index, left, top, width, height, x, y, 1.0, 1.0, [1.0, 1.0, 1.0, 1.0])?;
+        
+        default_return()
+    }
+    fn binding_draw_sprite_part_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 12
+        {
+            return Err("error: expected exactly 12 arguments to draw_sprite_part_ext()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let image_index = pop_front!(args, Number)?.floor() as u64;
+        let left = pop_front!(args, Number)?;
+        let top = pop_front!(args, Number)?;
+        let width = pop_front!(args, Number)?;
+        let height = pop_front!(args, Number)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let xscale = pop_front!(args, Number)? as f32;
+        let yscale = pop_front!(args, Number)? as f32;
+        let mut color = pop_color(&mut args)?;
+        color[3] *= pop_front!(args, Number)? as f32;
+        
+        self.draw_sprite_part(sprite_index, image_index, left, top, width, height, x, y, xscale, yscale, color)?;
+        
+        default_return()
+    }
+    
+    fn binding_sprite_set_filter(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to sprite_set_filter()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let linear = filter_from_name(&pop_front!(args, Text)?)?;
+        self.get_sprite_mut(sprite_index)?.sampler.linear = linear;
+        default_return()
+    }
+    fn binding_sprite_set_wrap(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to sprite_set_wrap()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let wrap = wrap_from_name(&pop_front!(args, Text)?)?;
+        self.get_sprite_mut(sprite_index)?.sampler.wrap = wrap;
+        default_return()
+    }
+    fn binding_sprite_set_mipmaps(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to sprite_set_mipmaps()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let mipmaps = pop_front!(args, Number)? != 0.0;
+        self.set_sprite_mipmaps(sprite_index, mipmaps)?;
+        default_return()
+    }
+    // durations are either one number of seconds for every frame, or an array with one number of seconds per frame
+    fn binding_sprite_add_animation(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 5
+        {
+            return Err("error: expected exactly 5 arguments to sprite_add_animation()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let name = pop_front!(args, Text)?;
+        let frames = pop_front!(args, Array)?.into_iter().map(|frame|
+        {
+            match frame
+            {
+                Value::Number(frame) if frame >= 0.0 => Ok(frame.floor() as usize),
+                _ => Err("error: animation frames must be non-negative numbers".to_string())
+            }
+        }).collect::<Result<Vec<usize>, String>>()?;
+        let durations = match args.remove(0)
+        {
+            Value::Number(duration) => vec!(duration; frames.len()),
+            Value::Array(durations) => durations.into_iter().map(|duration|
+            {
+                match duration
+                {
+                    Value::Number(duration) => Ok(duration),
+                    _ => Err("error: animation durations must be numbers".to_string())
+                }
+            }).collect::<Result<Vec<f64>, String>>()?,
+            _ => return Err("error: animation durations must be a number or an array of numbers".to_string())
+        };
+        let mode = LoopMode::from_name(&pop_front!(args, Text)?)?;
+        
+        self.add_sprite_animation(sprite_index, &name, Animation::new(frames, durations, mode)?)?;
+        default_return()
+    }
+    fn binding_sprite_get_animations(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to sprite_get_animations()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let mut names = self.get_sprite(sprite_index)?.animations().keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        Ok(Value::Array(Box::new(names.into_iter().map(Value::Text).collect())))
+    }
+    fn binding_animation_create(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to animation_create()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let name = pop_front!(args, Text)?;
+        
+        Ok(build_custom(5, self.create_animation_player(sprite_index, &name)?))
+    }
+    fn binding_animation_free(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_free()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        self.free_animation_player(animation_index)?;
+        default_return()
+    }
+    // switching to the animation that is already playing does not restart it
+    fn binding_animation_play(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to animation_play()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        let name = pop_front!(args, Text)?;
+        
+        if self.get_animation_player(animation_index)?.name != name
+        {
+            self.play_animation(animation_index, &name)?;
+        }
+        default_return()
+    }
+    fn binding_animation_restart(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_restart()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        self.get_animation_player_mut(animation_index)?.restart();
+        default_return()
+    }
+    fn binding_animation_set_paused(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to animation_set_paused()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        let paused = pop_front!(args, Number)? != 0.0;
+        self.get_animation_player_mut(animation_index)?.paused = paused;
+        default_return()
+    }
+    // 1 is normal speed; durations are divided by the speed
+    fn binding_animation_set_speed(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to animation_set_speed()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        let speed = pop_front!(args, Number)?;
+        self.get_animation_player_mut(animation_index)?.speed = speed;
+        default_return()
+    }
+    fn binding_animation_get_name(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_get_name()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        Ok(Value::Text(self.get_animation_player(animation_index)?.name.clone()))
+    }
+    fn binding_animation_get_image(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_get_image()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        Ok(Value::Number(self.get_animation_player(animation_index)?.image() as f64))
+    }
+    fn binding_animation_get_frame(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_get_frame()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        Ok(Value::Number(self.get_animation_player(animation_index)?.frame() as f64))
+    }
+    // only "once" animations ever finish; looping animations count their loops instead
+    fn binding_animation_is_finished(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_is_finished()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        Ok(Value::Number(self.get_animation_player(animation_index)?.finished() as u32 as f64))
+    }
+    fn binding_animation_get_loop_count(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to animation_get_loop_count()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        Ok(Value::Number(self.get_animation_player(animation_index)?.loops() as f64))
+    }
+    fn binding_draw_animation(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_animation()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        
+        self.draw_animation(animation_index, x, y, 1.0, 1.0, 0.0, [1.0, 1.0, 1.0, 1.0])?;
+        default_return()
+    }
+    fn binding_draw_animation_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 8
+        {
+            return Err("error: expected exactly 8 arguments to draw_animation_ext()".to_string());
+        }
+        let animation_index = match_custom(pop_front!(args, Custom)?, 5)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let xscale = pop_front!(args, Number)? as f32;
+        let yscale = pop_front!(args, Number)? as f32;
+        let angle = pop_front!(args, Number)? as f32;
+        let mut color = pop_color(&mut args)?;
+        color[3] *= pop_front!(args, Number)? as f32;
+        
+        self.draw_animation(animation_index, x, y, xscale, yscale, angle, color)?;
+        default_return()
+    }
+    fn binding_draw_sprite_tiled(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 7
+        {
+            return Err("error: expected exactly 7 arguments to draw_sprite_tiled()".to_string());
+        }
+        let sprite_index = match_custom(pop_front!(args, Custom)?, 0)?;
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let w = pop_front!(args, Number)?;
+        let h = pop_front!(args, Number)?;
+        let xoffset = pop_front!(args, Number)?;
+        let yoffset = pop_front!(args, Number)?;
+        
+        self.draw_sprite_tiled(sprite_index, x, y, w, h, (xoffset, yoffset), [1.0, 1.0, 1.0, 1.0])?;
+        default_return()
+    }
+    fn binding_draw_rectangle(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 6
+        {
+            return Err("error: expected exactly 6 arguments to draw_rectangle()".to_string());
+        }
+        let x1 = pop_front!(args, Number)? as f32;
+        let y1 = pop_front!(args, Number)? as f32;
+        let x2 = pop_front!(args, Number)? as f32;
+        let y2 = pop_front!(args, Number)? as f32;
+        let color = pop_color(&mut args)?;
+        let outline = pop_front!(args, Number)? as f32;
+        self.draw_rectangle(x1, y1, x2, y2, [color; 4], outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_rectangle_gradient(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 9
+        {
+            return Err("error: expected exactly 9 arguments to draw_rectangle_gradient()".to_string());
+        }
+        let x1 = pop_front!(args, Number)? as f32;
+        let y1 = pop_front!(args, Number)? as f32;
+        let x2 = pop_front!(args, Number)? as f32;
+        let y2 = pop_front!(args, Number)? as f32;
+        let color_topleft = pop_color(&mut args)?;
+        let color_topright = pop_color(&mut args)?;
+        let color_bottomright = pop_color(&mut args)?;
+        let color_bottomleft = pop_color(&mut args)?;
+        let outline = pop_front!(args, Number)? as f32;
+        self.draw_rectangle(x1, y1, x2, y2, [color_topleft, color_topright, color_bottomright, color_bottomleft], outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_line(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 6
+        {
+            return Err("error: expected exactly 6 arguments to draw_line()".to_string());
+        }
+        let x1 = pop_front!(args, Number)? as f32;
+        let y1 = pop_front!(args, Number)? as f32;
+        let x2 = pop_front!(args, Number)? as f32;
+        let y2 = pop_front!(args, Number)? as f32;
+        let width = pop_front!(args, Number)? as f32;
+        let color = pop_color(&mut args)?;
+        self.draw_line((x1, y1), (x2, y2), width, color, color)?;
+        
+        default_return()
+    }
+    fn binding_draw_line_gradient(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 7
+        {
+            return Err("error: expected exactly 7 arguments to draw_line_gradient()".to_string());
+        }
+        let x1 = pop_front!(args, Number)? as f32;
+        let y1 = pop_front!(args, Number)? as f32;
+        let x2 = pop_front!(args, Number)? as f32;
+        let y2 = pop_front!(args, Number)? as f32;
+        let width = pop_front!(args, Number)? as f32;
+        let color1 = pop_color(&mut args)?;
+        let color2 = pop_color(&mut args)?;
+        self.draw_line((x1, y1), (x2, y2), width, color1, color2)?;
+        
+        default_return()
+    }
+    fn binding_draw_circle(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 6
+        {
+            return Err("error: expected exactly 6 arguments to draw_circle()".to_string());
+        }
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let radius = pop_front!(args, Number)? as f32;
+        let segments = pop_front!(args, Number)?.max(0.0) as u32;
+        let color = pop_color(&mut args)?;
+        let outline = pop_front!(args, Number)? as f32;
+        self.draw_circle(x, y, radius, segments, color, color, outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_circle_gradient(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 7
+        {
+            return Err("error: expected exactly 7 arguments to draw_circle_gradient()".to_string());
+        }
+        let x = pop_front!(args, Number)? as f32;
+        let y = pop_front!(args, Number)? as f32;
+        let radius = pop_front!(args, Number)? as f32;
+        let segments = pop_front!(args, Number)?.max(0.0) as u32;
+        let color_center = pop_color(&mut args)?;
+        let color_edge = pop_color(&mut args)?;
+        let outline = pop_front!(args, Number)? as f32;
+        self.draw_circle(x, y, radius, segments, color_center, color_edge, outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_triangle(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 8
+        {
+            return Err("error: expected exactly 8 arguments to draw_triangle()".to_string());
+        }
+        let mut points = Vec::new();
+        for _ in 0..3
+        {
+            let x = pop_front!(args, Number)? as f32;
+            let y = pop_front!(args, Number)? as f32;
+            points.push((x, y));
+        }
+        let color = pop_color(&mut args)?;
+        let outline = pop_front!(args, Number)? as f32;
+        self.draw_polygon(&points, &[color; 3], outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_triangle_gradient(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 10
+        {
+            return Err("error: expected exactly 10 arguments to draw_triangle_gradient()".to_string());
+        }
+        let mut points = Vec::new();
+        for _ in 0..3
+        {
+            let x = pop_front!(args, Number)? as f32;
+            let y = pop_front!(args, Number)? as f32;
+            points.push((x, y));
+        }
+        let colors = [pop_color(&mut args)?, pop_color(&mut args)?, pop_color(&mut args)?];
+        let outline = pop_front!(args, Number)? as f32;
+        self.draw_polygon(&points, &colors, outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_polygon(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_polygon()".to_string());
+        }
+        let mut point_list = *pop_front!(args, Array)?;
+        let color = pop_color(&mut args)?;
+        let outline = pop_front!(args, Number)? as f32;
+        
+        let mut points = Vec::new();
+        while !point_list.is_empty()
+        {
+            points.push(pop_point(&mut point_list)?);
+        }
+        if points.len() < 3
+        {
+            return Err("error: draw_polygon must be given at least three points".to_string());
+        }
+        self.draw_polygon(&points, &vec!(color; points.len()), outline)?;
+        
+        default_return()
+    }
+    fn binding_draw_polygon_gradient(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to draw_polygon_gradient()".to_string());
+        }
+        let mut point_list = *pop_front!(args, Array)?;
+        let mut color_list = *pop_front!(args, Array)?;
+        let outline = pop_front!(args, Number)? as f32;
+        
+        let mut points = Vec::new();
+        while !point_list.is_empty()
+        {
+            points.push(pop_point(&mut point_list)?);
+        }
+        let mut colors = Vec::new();
+        while !color_list.is_empty()
+        {
+            colors.push(pop_color(&mut color_list)?);
+        }
+        if points.len() < 3
+        {
+            return Err("error: draw_polygon_gradient must be given at least three points".to_string());
+        }
+        if points.len() != colors.len()
+        {
+            return Err("error: draw_polygon_gradient must be given exactly one color per point".to_string());
+        }
+        self.draw_polygon(&points, &colors, outline)?;
+        
+        default_return()
+    }
+    
+    fn binding_draw_clear(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_clear()".to_string());
+        }
+        let mut color = pop_color(&mut args)?;
+        color[3] = 1.0;
+        self.clear_draw_target(color)?;
+        default_return()
+    }
+    fn binding_draw_clear_alpha(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to draw_clear_alpha()".to_string());
+        }
+        let mut color = pop_color(&mut args)?;
+        color[3] = pop_front!(args, Number)? as f32;
+        self.clear_draw_target(color)?;
+        default_return()
+    }
+    // the color that the default surface is cleared to at the start of every frame
+    fn binding_draw_set_background_color(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_background_color()".to_string());
+        }
+        self.clear_color = pop_color(&mut args)?;
+        default_return()
+    }
+    fn binding_draw_set_auto_clear(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_auto_clear()".to_string());
+        }
+        self.auto_clear = pop_front!(args, Number)? != 0.0;
+        default_return()
+    }
+    fn binding_draw_set_blend_mode(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_blend_mode()".to_string());
+        }
+        let name = pop_front!(args, Text)?;
+        self.draw_state.blend = blend_mode_from_name(&name)?;
+        default_return()
+    }
+    fn binding_draw_set_blend_mode_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 4
+        {
+            return Err("error: expected exactly 4 arguments to draw_set_blend_mode_ext()".to_string());
+        }
+        use glium::BlendingFunction::Addition;
+        let source = blend_factor_from_name(&pop_front!(args, Text)?)?;
+        let destination = blend_factor_from_name(&pop_front!(args, Text)?)?;
+        let source_alpha = blend_factor_from_name(&pop_front!(args, Text)?)?;
+        let destination_alpha = blend_factor_from_name(&pop_front!(args, Text)?)?;
+        self.draw_state.blend = glium::Blend
+        {
+            color : Addition{source, destination},
+            alpha : Addition{source : source_alpha, destination : destination_alpha},
+            ..Default::default()
+        };
+        default_return()
+    }
+    fn binding_draw_reset_blend_mode(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to draw_reset_blend_mode()".to_string());
+        }
+        self.draw_state.blend = DrawState::new().blend;
+        default_return()
+    }
+    fn binding_draw_set_depth_test(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_depth_test()".to_string());
+        }
+        let name = pop_front!(args, Text)?;
+        self.draw_state.depth_test = depth_test_from_name(&name)?;
+        default_return()
+    }
+    fn binding_draw_set_depth_write(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_depth_write()".to_string());
+        }
+        self.draw_state.depth_write = pop_front!(args, Number)? != 0.0;
+        default_return()
+    }
+    fn binding_draw_set_depth(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to draw_set_depth()".to_string());
+        }
+        let depth = pop_front!(args, Number)?;
+        if depth < -1.0 || depth > 1.0
+        {
+            return Err("error: depth given to draw_set_depth() must be between -1 and 1".to_string());
+        }
+        self.draw_state.depth_value = depth as f32;
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_draw_reset_depth(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to draw_reset_depth()".to_string());
+        }
+        let default = DrawState::new();
+        self.draw_state.depth_test = default.depth_test;
+        self.draw_state.depth_write = default.depth_write;
+        self.draw_state.depth_value = default.depth_value;
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_draw_set_scissor(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 4
+        {
+            return Err("error: expected exactly 4 arguments to draw_set_scissor()".to_string());
+        }
+        let x = pop_front!(args, Number)?;
+        let y = pop_front!(args, Number)?;
+        let w = pop_front!(args, Number)?;
+        let h = pop_front!(args, Number)?;
+        self.draw_state.scissor = Some((x, y, w, h));
+        default_return()
+    }
+    fn binding_draw_reset_scissor(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
         {
-            return Err("error: expected exactly 2 arguments to program_load()".to_string());
+            return Err("error: expected exactly 0 arguments to draw_reset_scissor()".to_string());
         }
-        let filename_vertex = pop_front!(args, Text)?;
-        let filename_fragment = pop_front!(args, Text)?;
-        
-        Ok(build_custom(1, self.load_program(&filename_vertex, &filename_fragment)?))
+        self.draw_state.scissor = None;
+        default_return()
     }
-    fn binding_program_set(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_draw_set_color_mask(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
-        if args.len() != 1
+        if args.len() != 4
         {
-            return Err("error: expected exactly 1 argument to program_set()".to_string());
+            return Err("error: expected exactly 4 arguments to draw_set_color_mask()".to_string());
         }
-        let program_index_wrapped = pop_front!(args, Custom)?;
-        let program_index = match_custom(program_index_wrapped, 1)?;
-        
-        self.set_program(program_index)?;
+        let r = pop_front!(args, Number)? != 0.0;
+        let g = pop_front!(args, Number)? != 0.0;
+        let b = pop_front!(args, Number)? != 0.0;
+        let a = pop_front!(args, Number)? != 0.0;
+        self.draw_state.color_mask = (r, g, b, a);
         default_return()
     }
-    fn binding_program_reset(&mut self, args : Vec<Value>) -> Result<Value, String>
+    fn binding_draw_reset_color_mask(&mut self, args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 0
         {
-            return Err("error: expected exactly 0 arguments to program_set()".to_string());
+            return Err("error: expected exactly 0 arguments to draw_reset_color_mask()".to_string());
         }
-        self.reset_program();
+        self.draw_state.color_mask = DrawState::new().color_mask;
         default_return()
     }
-    fn binding_file_load_to_string(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_draw_reset_state(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to draw_reset_state()".to_string());
+        }
+        self.draw_state = DrawState::new();
+        self.update_view_matrix();
+        default_return()
+    }
+    
+    fn binding_camera_create(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to camera_create()".to_string());
+        }
+        Ok(build_custom(3, self.create_camera()))
+    }
+    // drawing stops using the camera if it's the one being freed
+    fn binding_camera_free(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 1
         {
-            return Err("error: expected exactly 1 arguments to file_load_to_string()".to_string());
+            return Err("error: expected exactly 1 argument to camera_free()".to_string());
         }
-        let filename = pop_front!(args, Text)?;
-        
-        let string = load_string(&self.program_path, &self.prefix, &filename)?;
-        Ok(Value::Text(string))
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        self.free_camera(camera_index)?;
+        default_return()
     }
-    fn binding_draw_text(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_camera_set_position(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 3
         {
-            return Err("error: expected exactly 3 arguments to draw_text()".to_string());
+            return Err("error: expected exactly 3 arguments to camera_set_position()".to_string());
         }
-        let text = pop_front!(args, Text)?;
-        let x = pop_front!(args, Number)? as f32;
-        let y = pop_front!(args, Number)? as f32;
-        self.draw_text(&text, x, y, 999999999.0, 999999999.0, 24.0, [1.0, 1.0, 1.0, 1.0]);
-        
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let x = pop_front!(args, Number)?;
+        let y = pop_front!(args, Number)?;
+        let camera = self.get_camera_mut(camera_index)?;
+        camera.x = x;
+        camera.y = y;
+        self.update_view_matrix();
         default_return()
     }
-    fn binding_draw_text_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_camera_get_position(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
-        if args.len() != 6
+        if args.len() != 1
         {
-            return Err("error: expected exactly 6 arguments to draw_text_ext()".to_string());
+            return Err("error: expected exactly 1 argument to camera_get_position()".to_string());
         }
-        let text = pop_front!(args, Text)?;
-        let x = pop_front!(args, Number)? as f32;
-        let y = pop_front!(args, Number)? as f32;
-        let w = pop_front!(args, Number)? as f32;
-        let h = pop_front!(args, Number)? as f32;
-        let size = pop_front!(args, Number)? as f32;
-        self.draw_text(&text, x, y, w, h, size, [1.0, 1.0, 1.0, 1.0]);
-        
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let (x, y) = self.get_camera(camera_index)?.center(self.target_dims());
+        Ok(Value::Array(Box::new(vec!(Value::Number(x), Value::Number(y)))))
+    }
+    fn binding_camera_set_zoom(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to camera_set_zoom()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let zoom = pop_front!(args, Number)?;
+        if zoom <= 0.0
+        {
+            return Err("error: camera zoom must be greater than zero".to_string());
+        }
+        self.get_camera_mut(camera_index)?.zoom = zoom;
+        self.update_view_matrix();
         default_return()
     }
-    fn binding_sprite_load(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_camera_set_angle(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to camera_set_angle()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let angle = pop_front!(args, Number)?;
+        self.get_camera_mut(camera_index)?.angle = angle;
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_camera_set_bounds(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 5
+        {
+            return Err("error: expected exactly 5 arguments to camera_set_bounds()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let x1 = pop_front!(args, Number)?;
+        let y1 = pop_front!(args, Number)?;
+        let x2 = pop_front!(args, Number)?;
+        let y2 = pop_front!(args, Number)?;
+        self.get_camera_mut(camera_index)?.bounds = Some((x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)));
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_camera_clear_bounds(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to camera_clear_bounds()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        self.get_camera_mut(camera_index)?.bounds = None;
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_camera_set_viewport(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 5
+        {
+            return Err("error: expected exactly 5 arguments to camera_set_viewport()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let x = pop_front!(args, Number)?;
+        let y = pop_front!(args, Number)?;
+        let w = pop_front!(args, Number)?;
+        let h = pop_front!(args, Number)?;
+        if w <= 0.0 || h <= 0.0
+        {
+            return Err("error: camera viewport must have a positive size".to_string());
+        }
+        self.get_camera_mut(camera_index)?.viewport = Some((x, y, w, h));
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_camera_clear_viewport(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to camera_clear_viewport()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        self.get_camera_mut(camera_index)?.viewport = None;
+        self.update_view_matrix();
+        default_return()
+    }
+    fn binding_camera_apply(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to camera_apply()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        self.apply_camera(Some(camera_index))?;
+        default_return()
+    }
+    fn binding_camera_reset(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to camera_reset()".to_string());
+        }
+        self.apply_camera(None)?;
+        default_return()
+    }
+    fn binding_screen_to_world(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 3
         {
-            return Err("error: expected exactly 3 arguments to sprite_load()".to_string());
+            return Err("error: expected exactly 3 arguments to screen_to_world()".to_string());
         }
-        let filename = pop_front!(args, Text)?;
-        let xoffset = pop_front!(args, Number)?;
-        let yoffset = pop_front!(args, Number)?;
-        
-        Ok(build_custom(0, self.load_sprite(&filename, (xoffset, yoffset))))
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let x = pop_front!(args, Number)?;
+        let y = pop_front!(args, Number)?;
+        let (x, y) = self.get_camera(camera_index)?.screen_to_world(self.target_dims(), (x, y));
+        Ok(Value::Array(Box::new(vec!(Value::Number(x), Value::Number(y)))))
     }
-    fn binding_sprite_load_with_subimages(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_world_to_screen(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to world_to_screen()".to_string());
+        }
+        let camera_index = match_custom(pop_front!(args, Custom)?, 3)?;
+        let x = pop_front!(args, Number)?;
+        let y = pop_front!(args, Number)?;
+        let (x, y) = self.get_camera(camera_index)?.world_to_screen(self.target_dims(), (x, y));
+        Ok(Value::Array(Box::new(vec!(Value::Number(x), Value::Number(y)))))
+    }
+    
+    fn binding_surface_create(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 2
         {
-            return Err("error: expected exactly 2 arguments to sprite_load_with_subimages()".to_string());
+            return Err("error: expected exactly 2 arguments to surface_create()".to_string());
         }
-        let filename = pop_front!(args, Text)?;
-        let mut subimages = pop_front!(args, Array)?;
-        
-        let mut subimages_vec = Vec::new();
-        while !subimages.is_empty()
+        let w = pop_front!(args, Number)?.round();
+        let h = pop_front!(args, Number)?.round();
+        if w < 1.0 || h < 1.0
         {
-            let mut subimage = pop_front!(subimages, Array)?;
-            macro_rules! pop { () => { pop_front!(subimage, Number)? } }
-            subimages_vec.push(SpriteImage::extended((pop!(), pop!()), (pop!(), pop!()), (pop!(), pop!())));
+            return Err("error: surfaces must be at least 1x1 pixels in size".to_string());
         }
-        if subimages_vec.is_empty()
+        Ok(build_custom(4, self.create_surface((w as u32, h as u32))))
+    }
+    fn binding_surface_free(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
         {
-            return Err("error: sprite_load_with_subimages must be given at least one subimage".to_string());
+            return Err("error: expected exactly 1 argument to surface_free()".to_string());
         }
-        
-        Ok(build_custom(0, self.load_sprite_with_subimages(&filename, subimages_vec)))
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        self.free_surface(surface_index)?;
+        default_return()
     }
-    fn binding_font_load(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_surface_get_size(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 1
         {
-            return Err("error: expected exactly 1 arguments to font_load()".to_string());
+            return Err("error: expected exactly 1 argument to surface_get_size()".to_string());
         }
-        let filename = pop_front!(args, Text)?;
-        
-        Ok(build_custom(2, self.load_font(&filename)))
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        let (w, h) = self.surfaces.lookup(surface_index, "surface")?.dims;
+        Ok(Value::Array(Box::new(vec!(Value::Number(w.into()), Value::Number(h.into())))))
     }
-    fn binding_font_set(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_surface_set_filter(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to surface_set_filter()".to_string());
+        }
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        let linear = filter_from_name(&pop_front!(args, Text)?)?;
+        self.get_surface_mut(surface_index)?.sampler.linear = linear;
+        default_return()
+    }
+    fn binding_surface_set_wrap(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to surface_set_wrap()".to_string());
+        }
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        let wrap = wrap_from_name(&pop_front!(args, Text)?)?;
+        self.get_surface_mut(surface_index)?.sampler.wrap = wrap;
+        default_return()
+    }
+    // turning mipmaps on gives the surface room for them; they're regenerated the first time it is drawn after being drawn to
+    fn binding_surface_set_mipmaps(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 2
+        {
+            return Err("error: expected exactly 2 arguments to surface_set_mipmaps()".to_string());
+        }
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        let mipmaps = pop_front!(args, Number)? != 0.0;
+        self.surfaces.lookup_mut(surface_index, "surface")?.set_mipmaps(&self.display, mipmaps);
+        default_return()
+    }
+    // filtering used when the finished frame is presented to the window
+    fn binding_screen_set_filter(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 1
         {
-            return Err("error: expected exactly 1 arguments to font_set()".to_string());
+            return Err("error: expected exactly 1 argument to screen_set_filter()".to_string());
         }
-        let font_index_wrapped = pop_front!(args, Custom)?;
-        let font_index = match_custom(font_index_wrapped, 2)?;
-        self.set_font(font_index);
+        self.screen_sampler.linear = filter_from_name(&pop_front!(args, Text)?)?;
         default_return()
     }
-    fn binding_font_reset(&mut self, args : Vec<Value>) -> Result<Value, String>
+    fn binding_surface_set_target(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to surface_set_target()".to_string());
+        }
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        self.set_surface_target(surface_index)?;
+        default_return()
+    }
+    fn binding_surface_reset_target(&mut self, args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 0
         {
-            return Err("error: expected exactly 1 arguments to font_reset()".to_string());
+            return Err("error: expected exactly 0 arguments to surface_reset_target()".to_string());
         }
-        self.set_font(0);
+        self.reset_surface_target();
         default_return()
     }
-    fn binding_draw_sprite(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_draw_surface(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 3
         {
-            return Err("error: expected exactly 3 arguments to draw_sprite()".to_string());
+            return Err("error: expected exactly 3 arguments to draw_surface()".to_string());
         }
-        let sprite_index_wrapped = pop_front!(args, Custom)?;
-        let sprite_index = match_custom(sprite_index_wrapped, 0)?;
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
         let x = pop_front!(args, Number)? as f32;
         let y = pop_front!(args, Number)? as f32;
-        self.draw_sprite(sprite_index, 0, x, y);
-        
+        self.draw_surface(surface_index, x, y, 1.0, 1.0, [1.0, 1.0, 1.0, 1.0])?;
         default_return()
     }
-    fn binding_draw_sprite_scaled(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_draw_surface_ext(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
-        if args.len() != 5
+        if args.len() != 7
         {
-            return Err("error: expected exactly 5 arguments to draw_sprite_scaled()".to_string());
+            return Err("error: expected exactly 7 arguments to draw_surface_ext()".to_string());
         }
-        let sprite_index_wrapped = pop_front!(args, Custom)?;
-        let sprite_index = match_custom(sprite_index_wrapped, 0)?;
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
         let x = pop_front!(args, Number)? as f32;
         let y = pop_front!(args, Number)? as f32;
         let xscale = pop_front!(args, Number)? as f32;
         let yscale = pop_front!(args, Number)? as f32;
-        self.draw_sprite_scaled(sprite_index, 0, x, y, xscale, yscale);
-        
+        let mut color = pop_color(&mut args)?;
+        color[3] *= pop_front!(args, Number)? as f32;
+        self.draw_surface(surface_index, x, y, xscale, yscale, color)?;
         default_return()
     }
-    fn binding_draw_sprite_index(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    fn binding_surface_save(&mut self, mut args : Vec<Value>) -> Result<Value, String>
     {
-        if args.len() != 4
+        if args.len() != 2
         {
-            return Err("error: expected exactly 4 arguments to draw_sprite_index()".to_string());
+            return Err("error: expected exactly 2 arguments to surface_save()".to_string());
         }
-        let sprite_index_wrapped = pop_front!(args, Custom)?;
-        let sprite_index = match_custom(sprite_index_wrapped, 0)?;
-        
-        let image_index = pop_front!(args, Number)?.floor() as u64;
-        let x = pop_front!(args, Number)? as f32;
-        let y = pop_front!(args, Number)? as f32;
-        
-        self.draw_sprite(sprite_index, image_index, x, y);
-        
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        let filename = pop_front!(args, Text)?;
+        self.save_surface(Some(surface_index), &filename)?;
+        default_return()
+    }
+    fn binding_surface_get_pixels(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to surface_get_pixels()".to_string());
+        }
+        let surface_index = match_custom(pop_front!(args, Custom)?, 4)?;
+        let (pixels, _) = self.read_surface(Some(surface_index))?;
+        Ok(pixels_to_array(pixels))
+    }
+    fn binding_screen_save(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to screen_save()".to_string());
+        }
+        let filename = pop_front!(args, Text)?;
+        self.save_surface(None, &filename)?;
         default_return()
     }
+    fn binding_screen_get_pixels(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to screen_get_pixels()".to_string());
+        }
+        let (pixels, _) = self.read_surface(None)?;
+        Ok(pixels_to_array(pixels))
+    }
     
     fn binding_screen_size(&mut self, args : Vec<Value>) -> Result<Value, String>
     {
@@ -293,7 +2046,10 @@ impl Engine {
         {
             return Err("error: expected exactly 0 arguments to mouse_cursor_disable()".to_string());
         }
-        self.display.gl_window().window().hide_cursor(true);
+        if let Some(display) = self.display.window_display()
+        {
+            display.gl_window().window().hide_cursor(true);
+        }
         
         default_return()
     }
@@ -303,11 +2059,53 @@ impl Engine {
         {
             return Err("error: expected exactly 0 arguments to mouse_cursor_enable()".to_string());
         }
-        self.display.gl_window().window().hide_cursor(false);
+        if let Some(display) = self.display.window_display()
+        {
+            display.gl_window().window().hide_cursor(false);
+        }
         
         default_return()
     }
     
+    // the default surface stays at this size no matter how big the window is, and is scaled up to fit the window when presented
+    fn binding_screen_set_virtual_resolution(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 3
+        {
+            return Err("error: expected exactly 3 arguments to screen_set_virtual_resolution()".to_string());
+        }
+        let w = pop_front!(args, Number)?.max(0.0) as u32;
+        let h = pop_front!(args, Number)?.max(0.0) as u32;
+        let mode = ScaleMode::from_name(&pop_front!(args, Text)?)?;
+        self.set_virtual_resolution(Some(((w, h), mode)))?;
+        default_return()
+    }
+    fn binding_screen_reset_virtual_resolution(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to screen_reset_virtual_resolution()".to_string());
+        }
+        self.set_virtual_resolution(None)?;
+        default_return()
+    }
+    fn binding_screen_set_letterbox_color(&mut self, mut args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 1
+        {
+            return Err("error: expected exactly 1 argument to screen_set_letterbox_color()".to_string());
+        }
+        self.letterbox_color = pop_color(&mut args)?;
+        default_return()
+    }
+    fn binding_window_size(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to window_size()".to_string());
+        }
+        Ok(Value::Array(Box::new(vec!(Value::Number(self.window_dims.0.into()), Value::Number(self.window_dims.1.into())))))
+    }
     fn binding_mouse_position(&mut self, args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 0
@@ -317,6 +2115,15 @@ impl Engine {
         let (x, y) = self.input_handler.mouse_pos;
         Ok(Value::Array(Box::new(vec!(Value::Number(x), Value::Number(y)))))
     }
+    fn binding_mouse_position_window(&mut self, args : Vec<Value>) -> Result<Value, String>
+    {
+        if args.len() != 0
+        {
+            return Err("error: expected exactly 0 arguments to mouse_position_window()".to_string());
+        }
+        let (x, y) = self.input_handler.mouse_pos_window;
+        Ok(Value::Array(Box::new(vec!(Value::Number(x), Value::Number(y)))))
+    }
     fn binding_mouse_position_x(&mut self, args : Vec<Value>) -> Result<Value, String>
     {
         if args.len() != 0
@@ -503,27 +2310,162 @@ impl Engine {
         Engine::insert_binding(interpreter, engine, "sqrt", Engine::binding_sqrt);
         
         Engine::insert_binding(interpreter, engine, "program_load", Engine::binding_program_load);
+        Engine::insert_binding(interpreter, engine, "program_try_load", Engine::binding_program_try_load);
+        Engine::insert_binding(interpreter, engine, "program_get_errors", Engine::binding_program_get_errors);
         Engine::insert_binding(interpreter, engine, "program_set", Engine::binding_program_set);
         Engine::insert_binding(interpreter, engine, "program_reset", Engine::binding_program_reset);
+        Engine::insert_binding(interpreter, engine, "program_free", Engine::binding_program_free);
+        Engine::insert_binding(interpreter, engine, "postprocess_push", Engine::binding_postprocess_push);
+        Engine::insert_binding(interpreter, engine, "postprocess_set_uniform", Engine::binding_postprocess_set_uniform);
+        Engine::insert_binding(interpreter, engine, "postprocess_remove", Engine::binding_postprocess_remove);
+        Engine::insert_binding(interpreter, engine, "postprocess_clear", Engine::binding_postprocess_clear);
+        Engine::insert_binding(interpreter, engine, "postprocess_get_count", Engine::binding_postprocess_get_count);
         
         Engine::insert_binding(interpreter, engine, "file_load_to_string", Engine::binding_file_load_to_string);
+        Engine::insert_binding(interpreter, engine, "hotreload_enable", Engine::binding_hotreload_enable);
+        Engine::insert_binding(interpreter, engine, "hotreload_get_events", Engine::binding_hotreload_get_events);
         
         Engine::insert_binding(interpreter, engine, "sprite_load", Engine::binding_sprite_load);
         Engine::insert_binding(interpreter, engine, "sprite_load_with_subimages", Engine::binding_sprite_load_with_subimages);
+        Engine::insert_binding(interpreter, engine, "sprite_load_from_bytes", Engine::binding_sprite_load_from_bytes);
+        Engine::insert_binding(interpreter, engine, "sprite_load_strip", Engine::binding_sprite_load_strip);
+        Engine::insert_binding(interpreter, engine, "sprite_load_sheet", Engine::binding_sprite_load_sheet);
+        Engine::insert_binding(interpreter, engine, "sprite_free", Engine::binding_sprite_free);
+        Engine::insert_binding(interpreter, engine, "sprite_get_image_count", Engine::binding_sprite_get_image_count);
+        Engine::insert_binding(interpreter, engine, "sprite_get_image_duration", Engine::binding_sprite_get_image_duration);
+        Engine::insert_binding(interpreter, engine, "sprite_get_tags", Engine::binding_sprite_get_tags);
+        Engine::insert_binding(interpreter, engine, "sprite_add_animation", Engine::binding_sprite_add_animation);
+        Engine::insert_binding(interpreter, engine, "sprite_get_animations", Engine::binding_sprite_get_animations);
+        Engine::insert_binding(interpreter, engine, "sprite_set_filter", Engine::binding_sprite_set_filter);
+        Engine::insert_binding(interpreter, engine, "sprite_set_wrap", Engine::binding_sprite_set_wrap);
+        Engine::insert_binding(interpreter, engine, "sprite_set_mipmaps", Engine::binding_sprite_set_mipmaps);
+        
+        Engine::insert_binding(interpreter, engine, "animation_create", Engine::binding_animation_create);
+        Engine::insert_binding(interpreter, engine, "animation_free", Engine::binding_animation_free);
+        Engine::insert_binding(interpreter, engine, "animation_play", Engine::binding_animation_play);
+        Engine::insert_binding(interpreter, engine, "animation_restart", Engine::binding_animation_restart);
+        Engine::insert_binding(interpreter, engine, "animation_set_paused", Engine::binding_animation_set_paused);
+        Engine::insert_binding(interpreter, engine, "animation_set_speed", Engine::binding_animation_set_speed);
+        Engine::insert_binding(interpreter, engine, "animation_get_name", Engine::binding_animation_get_name);
+        Engine::insert_binding(interpreter, engine, "animation_get_image", Engine::binding_animation_get_image);
+        Engine::insert_binding(interpreter, engine, "animation_get_frame", Engine::binding_animation_get_frame);
+        Engine::insert_binding(interpreter, engine, "animation_is_finished", Engine::binding_animation_is_finished);
+        Engine::insert_binding(interpreter, engine, "animation_get_loop_count", Engine::binding_animation_get_loop_count);
         
         Engine::insert_binding(interpreter, engine, "font_load", Engine::binding_font_load);
+        Engine::insert_binding(interpreter, engine, "font_load_bitmap", Engine::binding_font_load_bitmap);
         Engine::insert_binding(interpreter, engine, "font_set", Engine::binding_font_set);
         Engine::insert_binding(interpreter, engine, "font_reset", Engine::binding_font_reset);
+        Engine::insert_binding(interpreter, engine, "font_free", Engine::binding_font_free);
+        Engine::insert_binding(interpreter, engine, "font_get_default", Engine::binding_font_get_default);
+        Engine::insert_binding(interpreter, engine, "font_set_name", Engine::binding_font_set_name);
+        Engine::insert_binding(interpreter, engine, "font_set_variant", Engine::binding_font_set_variant);
+        Engine::insert_binding(interpreter, engine, "font_set_fallbacks", Engine::binding_font_set_fallbacks);
         
         Engine::insert_binding(interpreter, engine, "draw_text", Engine::binding_draw_text);
         Engine::insert_binding(interpreter, engine, "draw_text_ext", Engine::binding_draw_text_ext);
+        Engine::insert_binding(interpreter, engine, "draw_text_color", Engine::binding_draw_text_color);
+        Engine::insert_binding(interpreter, engine, "draw_text_ext_color", Engine::binding_draw_text_ext_color);
+        Engine::insert_binding(interpreter, engine, "draw_text_rich", Engine::binding_draw_text_rich);
+        Engine::insert_binding(interpreter, engine, "draw_text_rich_ext", Engine::binding_draw_text_rich_ext);
+        Engine::insert_binding(interpreter, engine, "draw_text_reveal", Engine::binding_draw_text_reveal);
+        Engine::insert_binding(interpreter, engine, "draw_text_reveal_time", Engine::binding_draw_text_reveal_time);
+        Engine::insert_binding(interpreter, engine, "draw_set_text_wave", Engine::binding_draw_set_text_wave);
+        Engine::insert_binding(interpreter, engine, "draw_set_text_shake", Engine::binding_draw_set_text_shake);
+        Engine::insert_binding(interpreter, engine, "draw_set_text_outline", Engine::binding_draw_set_text_outline);
+        Engine::insert_binding(interpreter, engine, "draw_set_text_shadow", Engine::binding_draw_set_text_shadow);
+        Engine::insert_binding(interpreter, engine, "draw_reset_text_shadow", Engine::binding_draw_reset_text_shadow);
+        Engine::insert_binding(interpreter, engine, "draw_set_color", Engine::binding_draw_set_color);
+        Engine::insert_binding(interpreter, engine, "draw_set_alpha", Engine::binding_draw_set_alpha);
+        Engine::insert_binding(interpreter, engine, "draw_get_color", Engine::binding_draw_get_color);
+        Engine::insert_binding(interpreter, engine, "draw_set_halign", Engine::binding_draw_set_halign);
+        Engine::insert_binding(interpreter, engine, "draw_set_valign", Engine::binding_draw_set_valign);
+        Engine::insert_binding(interpreter, engine, "draw_set_text_size", Engine::binding_draw_set_text_size);
+        
+        Engine::insert_binding(interpreter, engine, "string_width", Engine::binding_string_width);
+        Engine::insert_binding(interpreter, engine, "string_height", Engine::binding_string_height);
+        Engine::insert_binding(interpreter, engine, "string_width_ext", Engine::binding_string_width_ext);
+        Engine::insert_binding(interpreter, engine, "string_height_ext", Engine::binding_string_height_ext);
+        Engine::insert_binding(interpreter, engine, "string_get_lines", Engine::binding_string_get_lines);
+        Engine::insert_binding(interpreter, engine, "string_glyph_count", Engine::binding_string_glyph_count);
+        Engine::insert_binding(interpreter, engine, "font_get_metrics", Engine::binding_font_get_metrics);
         Engine::insert_binding(interpreter, engine, "draw_sprite", Engine::binding_draw_sprite);
         Engine::insert_binding(interpreter, engine, "draw_sprite_scaled", Engine::binding_draw_sprite_scaled);
         Engine::insert_binding(interpreter, engine, "draw_sprite_index", Engine::binding_draw_sprite_index);
+        Engine::insert_binding(interpreter, engine, "draw_sprite_ext", Engine::binding_draw_sprite_ext);
+        Engine::insert_binding(interpreter, engine, "draw_sprite_matrix", Engine::binding_draw_sprite_matrix);
+        Engine::insert_binding(interpreter, engine, "draw_sprite_part", Engine::binding_draw_sprite_part);
+        Engine::insert_binding(interpreter, engine, "draw_sprite_part_ext", Engine::binding_draw_sprite_part_ext);
+        Engine::insert_binding(interpreter, engine, "draw_sprite_tiled", Engine::binding_draw_sprite_tiled);
+        Engine::insert_binding(interpreter, engine, "draw_animation", Engine::binding_draw_animation);
+        Engine::insert_binding(interpreter, engine, "draw_animation_ext", Engine::binding_draw_animation_ext);
+        
+        Engine::insert_binding(interpreter, engine, "draw_rectangle", Engine::binding_draw_rectangle);
+        Engine::insert_binding(interpreter, engine, "draw_rectangle_gradient", Engine::binding_draw_rectangle_gradient);
+        Engine::insert_binding(interpreter, engine, "draw_line", Engine::binding_draw_line);
+        Engine::insert_binding(interpreter, engine, "draw_line_gradient", Engine::binding_draw_line_gradient);
+        Engine::insert_binding(interpreter, engine, "draw_circle", Engine::binding_draw_circle);
+        Engine::insert_binding(interpreter, engine, "draw_circle_gradient", Engine::binding_draw_circle_gradient);
+        Engine::insert_binding(interpreter, engine, "draw_triangle", Engine::binding_draw_triangle);
+        Engine::insert_binding(interpreter, engine, "draw_triangle_gradient", Engine::binding_draw_triangle_gradient);
+        Engine::insert_binding(interpreter, engine, "draw_polygon", Engine::binding_draw_polygon);
+        Engine::insert_binding(interpreter, engine, "draw_polygon_gradient", Engine::binding_draw_polygon_gradient);
+        
+        Engine::insert_binding(interpreter, engine, "draw_set_blend_mode", Engine::binding_draw_set_blend_mode);
+        Engine::insert_binding(interpreter, engine, "draw_set_blend_mode_ext", Engine::binding_draw_set_blend_mode_ext);
+        Engine::insert_binding(interpreter, engine, "draw_reset_blend_mode", Engine::binding_draw_reset_blend_mode);
+        Engine::insert_binding(interpreter, engine, "draw_set_depth_test", Engine::binding_draw_set_depth_test);
+        Engine::insert_binding(interpreter, engine, "draw_set_depth_write", Engine::binding_draw_set_depth_write);
+        Engine::insert_binding(interpreter, engine, "draw_set_depth", Engine::binding_draw_set_depth);
+        Engine::insert_binding(interpreter, engine, "draw_reset_depth", Engine::binding_draw_reset_depth);
+        Engine::insert_binding(interpreter, engine, "draw_set_scissor", Engine::binding_draw_set_scissor);
+        Engine::insert_binding(interpreter, engine, "draw_reset_scissor", Engine::binding_draw_reset_scissor);
+        Engine::insert_binding(interpreter, engine, "draw_set_color_mask", Engine::binding_draw_set_color_mask);
+        Engine::insert_binding(interpreter, engine, "draw_reset_color_mask", Engine::binding_draw_reset_color_mask);
+        Engine::insert_binding(interpreter, engine, "draw_reset_state", Engine::binding_draw_reset_state);
+        Engine::insert_binding(interpreter, engine, "draw_clear", Engine::binding_draw_clear);
+        Engine::insert_binding(interpreter, engine, "draw_clear_alpha", Engine::binding_draw_clear_alpha);
+        Engine::insert_binding(interpreter, engine, "draw_set_background_color", Engine::binding_draw_set_background_color);
+        Engine::insert_binding(interpreter, engine, "draw_set_auto_clear", Engine::binding_draw_set_auto_clear);
+        
+        Engine::insert_binding(interpreter, engine, "camera_create", Engine::binding_camera_create);
+        Engine::insert_binding(interpreter, engine, "camera_free", Engine::binding_camera_free);
+        Engine::insert_binding(interpreter, engine, "camera_set_position", Engine::binding_camera_set_position);
+        Engine::insert_binding(interpreter, engine, "camera_get_position", Engine::binding_camera_get_position);
+        Engine::insert_binding(interpreter, engine, "camera_set_zoom", Engine::binding_camera_set_zoom);
+        Engine::insert_binding(interpreter, engine, "camera_set_angle", Engine::binding_camera_set_angle);
+        Engine::insert_binding(interpreter, engine, "camera_set_bounds", Engine::binding_camera_set_bounds);
+        Engine::insert_binding(interpreter, engine, "camera_clear_bounds", Engine::binding_camera_clear_bounds);
+        Engine::insert_binding(interpreter, engine, "camera_set_viewport", Engine::binding_camera_set_viewport);
+        Engine::insert_binding(interpreter, engine, "camera_clear_viewport", Engine::binding_camera_clear_viewport);
+        Engine::insert_binding(interpreter, engine, "camera_apply", Engine::binding_camera_apply);
+        Engine::insert_binding(interpreter, engine, "camera_reset", Engine::binding_camera_reset);
+        Engine::insert_binding(interpreter, engine, "screen_to_world", Engine::binding_screen_to_world);
+        Engine::insert_binding(interpreter, engine, "world_to_screen", Engine::binding_world_to_screen);
+        
+        Engine::insert_binding(interpreter, engine, "surface_create", Engine::binding_surface_create);
+        Engine::insert_binding(interpreter, engine, "surface_free", Engine::binding_surface_free);
+        Engine::insert_binding(interpreter, engine, "surface_get_size", Engine::binding_surface_get_size);
+        Engine::insert_binding(interpreter, engine, "surface_set_filter", Engine::binding_surface_set_filter);
+        Engine::insert_binding(interpreter, engine, "surface_set_wrap", Engine::binding_surface_set_wrap);
+        Engine::insert_binding(interpreter, engine, "surface_set_mipmaps", Engine::binding_surface_set_mipmaps);
+        Engine::insert_binding(interpreter, engine, "surface_set_target", Engine::binding_surface_set_target);
+        Engine::insert_binding(interpreter, engine, "surface_reset_target", Engine::binding_surface_reset_target);
+        Engine::insert_binding(interpreter, engine, "draw_surface", Engine::binding_draw_surface);
+        Engine::insert_binding(interpreter, engine, "draw_surface_ext", Engine::binding_draw_surface_ext);
+        Engine::insert_binding(interpreter, engine, "surface_save", Engine::binding_surface_save);
+        Engine::insert_binding(interpreter, engine, "surface_get_pixels", Engine::binding_surface_get_pixels);
+        Engine::insert_binding(interpreter, engine, "screen_save", Engine::binding_screen_save);
+        Engine::insert_binding(interpreter, engine, "screen_get_pixels", Engine::binding_screen_get_pixels);
+        Engine::insert_binding(interpreter, engine, "screen_set_filter", Engine::binding_screen_set_filter);
         
         Engine::insert_binding(interpreter, engine, "screen_size", Engine::binding_screen_size);
         Engine::insert_binding(interpreter, engine, "screen_size_w", Engine::binding_screen_size_w);
         Engine::insert_binding(interpreter, engine, "screen_size_h", Engine::binding_screen_size_h);
+        Engine::insert_binding(interpreter, engine, "screen_set_virtual_resolution", Engine::binding_screen_set_virtual_resolution);
+        Engine::insert_binding(interpreter, engine, "screen_reset_virtual_resolution", Engine::binding_screen_reset_virtual_resolution);
+        Engine::insert_binding(interpreter, engine, "screen_set_letterbox_color", Engine::binding_screen_set_letterbox_color);
+        Engine::insert_binding(interpreter, engine, "window_size", Engine::binding_window_size);
         
         Engine::insert_binding(interpreter, engine, "key_down", Engine::binding_key_down);
         Engine::insert_binding(interpreter, engine, "key_pressed", Engine::binding_key_pressed);
@@ -532,6 +2474,7 @@ impl Engine {
         Engine::insert_binding(interpreter, engine, "mouse_position", Engine::binding_mouse_position);
         Engine::insert_binding(interpreter, engine, "mouse_position_x", Engine::binding_mouse_position_x);
         Engine::insert_binding(interpreter, engine, "mouse_position_y", Engine::binding_mouse_position_y);
+        Engine::insert_binding(interpreter, engine, "mouse_position_window", Engine::binding_mouse_position_window);
         Engine::insert_binding(interpreter, engine, "mouse_button_down", Engine::binding_mouse_button_down);
         Engine::insert_binding(interpreter, engine, "mouse_button_pressed", Engine::binding_mouse_button_pressed);
         Engine::insert_binding(interpreter, engine, "mouse_button_released", Engine::binding_mouse_button_released);
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use super::linebreak::{layout_lines, WrapMode};
+
+// where a glyph is on its font's sprite, in texture pixels, and how it sits on the line
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct BitmapGlyph {
+    pub (super) topleft: (f64, f64),
+    pub (super) bottomright: (f64, f64),
+    pub (super) offset: (f32, f32), // from the pen position at the top of the line
+    pub (super) advance: f32,
+}
+
+// a font drawn from a sprite; sizes are in the sprite's pixels, and get scaled by the size that text is drawn at over line_height
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct BitmapFont {
+    pub (super) glyphs: HashMap<char, BitmapGlyph>,
+    pub (super) kerning: HashMap<(char, char), f32>,
+    pub (super) line_height: f32,
+    pub (super) base: f32,
+    pub (super) page: Option<String>, // the image file that a .fnt file refers to
+}
+
+// one glyph placed by layout, relative to the point the text is drawn at
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct PlacedGlyph {
+    pub (super) ch: char,
+    pub (super) index: usize, // of the char in the text
+    pub (super) x: f32,
+    pub (super) y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct BitmapLayout {
+    pub (super) glyphs: Vec<PlacedGlyph>,
+    pub (super) width: f32,
+    pub (super) height: f32,
+}
+
+// the key=value pairs on one line of a .fnt file; values can be quoted
+fn parse_fnt_fields(line : &str) -> (String, HashMap<String, String>)
+{
+    let line = line.trim();
+    let (tag, mut rest) = match line.find(' ')
+    {
+        Some(split) => (&line[..split], line[split..].trim_start()),
+        None => (line, "")
+    };
+    let mut fields = HashMap::new();
+    while let Some(eq) = rest.find('=')
+    {
+        let key = rest[..eq].trim().to_string();
+        rest = &rest[eq + 1..];
+        let value = if rest.starts_with('"')
+        {
+            let end = rest[1..].find('"').map(|end| end + 1).unwrap_or(rest.len());
+            let value = rest[1..end].to_string();
+            rest = &rest[(end + 1).min(rest.len())..];
+            value
+        }
+        else
+        {
+            let end = rest.find(' ').unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            value
+        };
+        fields.insert(key, value);
+        rest = rest.trim_start();
+    }
+    (tag.to_string(), fields)
+}
+
+fn fnt_number(fields : &HashMap<String, String>, key : &str, line : usize) -> Result<f32, String>
+{
+    let value = fields.get(key).ok_or_else(|| format!("error: line {} of bitmap font is missing `{}`", line, key))?;
+    value.parse::<f32>().or_else(|_| Err(format!("error: line {} of bitmap font has a bad value for `{}`: `{}`", line, key, value)))
+}
+
+fn fnt_char(fields : &HashMap<String, String>, key : &str, line : usize) -> Result<char, String>
+{
+    let id = fnt_number(fields, key, line)?;
+    std::char::from_u32(id as u32).ok_or_else(|| format!("error: line {} of bitmap font has an invalid character id {}", line, id))
+}
+
+impl BitmapFont {
+    // each image of the sprite is the glyph for the char at the same position in `chars`, and glyphs are spaced `spacing` pixels apart
+    pub (super) fn from_images(chars : &str, images : &[((f64, f64), (f64, f64))], spacing : f32) -> Result<BitmapFont, String>
+    {
+        let chars : Vec<char> = chars.chars().collect();
+        if chars.len() > images.len()
+        {
+            return Err(format!("error: bitmap font has {} characters but its sprite only has {} images", chars.len(), images.len()));
+        }
+        let mut glyphs = HashMap::new();
+        let mut line_height : f32 = 0.0;
+        for (ch, (topleft, bottomright)) in chars.iter().zip(images.iter())
+        {
+            let (w, h) = ((bottomright.0 - topleft.0) as f32, (bottomright.1 - topleft.1) as f32);
+            line_height = line_height.max(h);
+            glyphs.insert(*ch, BitmapGlyph{topleft : *topleft, bottomright : *bottomright, offset : (0.0, 0.0), advance : w + spacing});
+        }
+        if !glyphs.contains_key(&' ')
+        {
+            // sheets rarely have a blank cell, so spaces are as wide as an average glyph
+            let advance = glyphs.values().map(|glyph| glyph.advance).sum::<f32>()/(glyphs.len().max(1) as f32);
+            glyphs.insert(' ', BitmapGlyph{topleft : (0.0, 0.0), bottomright : (0.0, 0.0), offset : (0.0, 0.0), advance : advance.round()});
+        }
+        Ok(BitmapFont{glyphs, kerning : HashMap::new(), line_height : line_height.max(1.0), base : line_height, page : None})
+    }
+    // the text format of AngelCode BMFont files, with a single page
+    pub (super) fn from_fnt(text : &str) -> Result<BitmapFont, String>
+    {
+        let mut font = BitmapFont{glyphs : HashMap::new(), kerning : HashMap::new(), line_height : 0.0, base : 0.0, page : None};
+        for (number, line) in text.lines().enumerate().map(|(number, line)| (number + 1, line))
+        {
+            let (tag, fields) = parse_fnt_fields(line);
+            match tag.as_str()
+            {
+                "common" =>
+                {
+                    font.line_height = fnt_number(&fields, "lineHeight", number)?;
+                    font.base = fnt_number(&fields, "base", number)?;
+                    if fields.get("pages").map(|pages| pages != "1").unwrap_or(false)
+                    {
+                        return Err("error: bitmap fonts with more than one page are not supported".to_string());
+                    }
+                }
+                "page" => font.page = Some(fields.get("file").ok_or_else(|| format!("error: line {} of bitmap font is missing `file`", number))?.clone()),
+                "char" =>
+                {
+                    let (x, y) = (fnt_number(&fields, "x", number)? as f64, fnt_number(&fields, "y", number)? as f64);
+                    let (w, h) = (fnt_number(&fields, "width", number)? as f64, fnt_number(&fields, "height", number)? as f64);
+                    let glyph = BitmapGlyph {
+                        topleft : (x, y),
+                        bottomright : (x + w, y + h),
+                        offset : (fnt_number(&fields, "xoffset", number)?, fnt_number(&fields, "yoffset", number)?),
+                        advance : fnt_number(&fields, "xadvance", number)?,
+                    };
+                    font.glyphs.insert(fnt_char(&fields, "id", number)?, glyph);
+                }
+                "kerning" =>
+                {
+                    let pair = (fnt_char(&fields, "first", number)?, fnt_char(&fields, "second", number)?);
+                    font.kerning.insert(pair, fnt_number(&fields, "amount", number)?);
+                }
+                _ => {}
+            }
+        }
+        if !(font.line_height > 0.0)
+        {
+            return Err("error: bitmap font has no `common` line with a line height".to_string());
+        }
+        if font.page.is_none()
+        {
+            return Err("error: bitmap font has no `page` line".to_string());
+        }
+        Ok(font)
+    }
+    fn glyph(&self, ch : char) -> Option<&BitmapGlyph>
+    {
+        self.glyphs.get(&ch).or_else(|| self.glyphs.get(&'?'))
+    }
+    fn kerning(&self, prev : Option<char>, ch : char) -> f32
+    {
+        prev.and_then(|prev| self.kerning.get(&(prev, ch))).cloned().unwrap_or(0.0)
+    }
+    fn advance(&self, ch : char) -> f32
+    {
+        self.glyph(ch).map(|glyph| glyph.advance).unwrap_or(0.0)
+    }
+    // the width of a line of text, from the advances and kerning of its chars
+    fn line_width(&self, line : &str, scale : f32) -> f32
+    {
+        let mut prev = None;
+        line.chars().map(|ch|
+        {
+            let width = (self.kerning(prev, ch) + self.advance(ch))*scale;
+            prev = Some(ch);
+            width
+        }).sum()
+    }
+    // breaks lines the same way as draw_text_ext does to fit max_width, which is in drawn pixels like everything else here
+    // like glyph_brush layouts, halign and valign place the text and its box around its position: 0 for left/top, 0.5 for center, 1 for right/bottom
+    // and lines that are completely outside of the box's height are left out
+    pub (super) fn layout(&self, text : &str, max_width : f32, max_height : f32, scale : f32, halign : f32, valign : f32) -> BitmapLayout
+    {
+        let measure = |line : &str| self.line_width(line, scale);
+        let lines = layout_lines(text, max_width, None, WrapMode::Word, false, &measure);
+        
+        let line_height = self.line_height*scale;
+        let width = lines.iter().map(|line| measure(&text[line.line.clone()])).fold(0.0, f32::max);
+        let height = lines.len() as f32*line_height;
+        let top = -height*valign;
+        let (box_top, box_bottom) = (-max_height*valign, max_height*(1.0 - valign));
+        let mut layout = BitmapLayout{glyphs : Vec::new(), width, height};
+        for (number, line) in lines.iter().enumerate()
+        {
+            let y = top + number as f32*line_height;
+            if y >= box_bottom || y + line_height <= box_top
+            {
+                continue;
+            }
+            let shown = &text[line.line.clone()];
+            let left = -measure(shown)*halign;
+            let first_index = text[..line.line.start].chars().count();
+            let (mut pen, mut prev) = (0.0, None);
+            for (index, ch) in shown.chars().enumerate()
+            {
+                pen += self.kerning(prev, ch)*scale;
+                if self.glyph(ch).is_some()
+                {
+                    layout.glyphs.push(PlacedGlyph{ch, index : first_index + index, x : left + pen, y});
+                }
+                pen += self.advance(ch)*scale;
+                prev = Some(ch);
+            }
+        }
+        layout
+    }
+    // where to draw a placed glyph's image, and which part of the sprite it comes from
+    pub (super) fn glyph_rect(&self, placed : &PlacedGlyph, scale : f32) -> Option<((f32, f32), (f64, f64), (f64, f64))>
+    {
+        let glyph = self.glyph(placed.ch)?;
+        if glyph.bottomright.0 <= glyph.topleft.0 || glyph.bottomright.1 <= glyph.topleft.1
+        {
+            return None;
+        }
+        Some(((placed.x + glyph.offset.0*scale, placed.y + glyph.offset.1*scale), glyph.topleft, glyph.bottomright))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn test_font() -> BitmapFont
+    {
+        let images : Vec<((f64, f64), (f64, f64))> = (0..4).map(|i| ((i as f64*8.0, 0.0), (i as f64*8.0 + 8.0, 10.0))).collect();
+        BitmapFont::from_images("abcd", &images, 0.0).unwrap()
+    }
+    
+    #[test]
+    fn test_parse_fnt()
+    {
+        let text = "info face=\"Pixel Font\" size=10\ncommon lineHeight=12 base=9 scaleW=64 scaleH=64 pages=1\npage id=0 file=\"pixel_0.png\"\nchars count=2\nchar id=65 x=0 y=0 width=6 height=8 xoffset=1 yoffset=2 xadvance=7 page=0\nchar id=86 x=6 y=0 width=6 height=8 xoffset=0 yoffset=2 xadvance=7 page=0\nkernings count=1\nkerning first=65 second=86 amount=-1\n";
+        let font = BitmapFont::from_fnt(text).unwrap();
+        assert_eq!(font.line_height, 12.0);
+        assert_eq!(font.page, Some("pixel_0.png".to_string()));
+        assert_eq!(font.glyphs[&'A'], BitmapGlyph{topleft : (0.0, 0.0), bottomright : (6.0, 8.0), offset : (1.0, 2.0), advance : 7.0});
+        let layout = font.layout("AV", 1000.0, 1000.0, 1.0, 0.0, 0.0);
+        assert_eq!(layout.glyphs[1].x, 6.0);
+        assert_eq!(layout.width, 13.0);
+        assert!(BitmapFont::from_fnt("common lineHeight=12 base=9 pages=2\n").is_err());
+    }
+    
+    #[test]
+    fn test_wrapping()
+    {
+        let font = test_font();
+        // spaces are as wide as the other glyphs; "ab cd" is 40 wide and doesn't fit in 30
+        let layout = font.layout("ab cd", 30.0, 1000.0, 1.0, 0.0, 0.0);
+        assert_eq!((layout.width, layout.height), (16.0, 20.0));
+        let positions : Vec<(char, f32, f32)> = layout.glyphs.iter().filter(|glyph| glyph.ch != ' ').map(|glyph| (glyph.ch, glyph.x, glyph.y)).collect();
+        assert_eq!(positions, vec!(('a', 0.0, 0.0), ('b', 8.0, 0.0), ('c', 0.0, 10.0), ('d', 8.0, 10.0)));
+        assert_eq!(font.layout("ab\ncd", 1000.0, 1000.0, 2.0, 0.0, 0.0).height, 40.0);
+        // words too long for a line are broken between chars, like draw_text_ext does
+        let layout = font.layout("abcd", 20.0, 1000.0, 1.0, 0.0, 0.0);
+        assert_eq!(layout.glyphs.iter().map(|glyph| (glyph.index, glyph.y)).collect::<Vec<_>>(), vec!((0, 0.0), (1, 0.0), (2, 10.0), (3, 10.0)));
+    }
+    
+    #[test]
+    fn test_height_clipping()
+    {
+        let font = test_font();
+        // the third line starts at the bottom of the box, so it's left out, but the second one is partly inside of it
+        let layout = font.layout("a\nb\nc", 1000.0, 15.0, 1.0, 0.0, 0.0);
+        assert_eq!(layout.glyphs.iter().map(|glyph| glyph.ch).collect::<String>(), "ab");
+        assert_eq!(layout.height, 30.0);
+        // bottom aligned text is cut off at the top of its box instead
+        let layout = font.layout("a\nb\nc", 1000.0, 10.0, 1.0, 0.0, 1.0);
+        assert_eq!(layout.glyphs.iter().map(|glyph| (glyph.ch, glyph.index, glyph.y)).collect::<Vec<_>>(), vec!(('c', 4, -10.0)));
+    }
+    
+    #[test]
+    fn test_alignment()
+    {
+        let font = test_font();
+        let layout = font.layout("a\nabc", 1000.0, 1000.0, 1.0, 0.5, 1.0);
+        let positions : Vec<(char, f32, f32)> = layout.glyphs.iter().map(|glyph| (glyph.ch, glyph.x, glyph.y)).collect();
+        assert_eq!(positions, vec!(('a', -4.0, -20.0), ('a', -12.0, -10.0), ('b', -4.0, -10.0), ('c', 4.0, -10.0)));
+    }
+}
@@ -0,0 +1,155 @@
+// A camera looks at the world with its position at the center of the view.
+// The view is either the whole draw target or a viewport rectangle on it, given in target pixels from the top left.
+#[derive(Debug, Clone)]
+pub (super) struct Camera {
+    pub (super) x: f64,
+    pub (super) y: f64,
+    pub (super) zoom: f64,
+    pub (super) angle: f64, // degrees
+    pub (super) bounds: Option<(f64, f64, f64, f64)>,
+    pub (super) viewport: Option<(f64, f64, f64, f64)>,
+}
+
+impl Camera {
+    pub (super) fn new() -> Camera
+    {
+        Camera{x : 0.0, y : 0.0, zoom : 1.0, angle : 0.0, bounds : None, viewport : None}
+    }
+    pub (super) fn view_rect(&self, target_dims : (u32, u32)) -> (f64, f64, f64, f64)
+    {
+        self.viewport.unwrap_or((0.0, 0.0, target_dims.0 as f64, target_dims.1 as f64))
+    }
+    // keeps the unrotated view inside of the bounds, or centers it on the bounds if the view is larger than them
+    pub (super) fn center(&self, target_dims : (u32, u32)) -> (f64, f64)
+    {
+        if let Some((x1, y1, x2, y2)) = self.bounds
+        {
+            let (_, _, w, h) = self.view_rect(target_dims);
+            let half_w = w/2.0/self.zoom;
+            let half_h = h/2.0/self.zoom;
+            let clamp = |pos : f64, low : f64, high : f64, half : f64| -> f64
+            {
+                if high - low < half*2.0
+                {
+                    (low + high)/2.0
+                }
+                else
+                {
+                    pos.max(low + half).min(high - half)
+                }
+            };
+            (clamp(self.x, x1, x2, half_w), clamp(self.y, y1, y2, half_h))
+        }
+        else
+        {
+            (self.x, self.y)
+        }
+    }
+    // maps world coordinates to normalized device coordinates inside of the view rectangle
+    pub (super) fn view_matrix(&self, target_dims : (u32, u32)) -> [[f32; 4]; 4]
+    {
+        let (_, _, w, h) = self.view_rect(target_dims);
+        let (cx, cy) = self.center(target_dims);
+        let angle = self.angle.to_radians();
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let zoom = self.zoom;
+        
+        let a = 2.0*zoom*cos/w;
+        let b = 2.0*zoom*sin/w;
+        let c = 2.0*zoom*sin/h;
+        let d = -2.0*zoom*cos/h;
+        [
+            [a as f32, c as f32, 0.0, 0.0],
+            [b as f32, d as f32, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [(-a*cx - b*cy) as f32, (-c*cx - d*cy) as f32, 0.0, 1.0],
+        ]
+    }
+    pub (super) fn screen_to_world(&self, target_dims : (u32, u32), (x, y) : (f64, f64)) -> (f64, f64)
+    {
+        let (vx, vy, w, h) = self.view_rect(target_dims);
+        let (cx, cy) = self.center(target_dims);
+        let angle = self.angle.to_radians();
+        let (sin, cos) = (angle.sin(), angle.cos());
+        
+        let local_x = (x - vx - w/2.0)/self.zoom;
+        let local_y = (y - vy - h/2.0)/self.zoom;
+        (cx + cos*local_x - sin*local_y, cy + sin*local_x + cos*local_y)
+    }
+    pub (super) fn world_to_screen(&self, target_dims : (u32, u32), (x, y) : (f64, f64)) -> (f64, f64)
+    {
+        let (vx, vy, w, h) = self.view_rect(target_dims);
+        let (cx, cy) = self.center(target_dims);
+        let angle = self.angle.to_radians();
+        let (sin, cos) = (angle.sin(), angle.cos());
+        
+        let (dx, dy) = (x - cx, y - cy);
+        let local_x = (cos*dx + sin*dy)*self.zoom;
+        let local_y = (-sin*dx + cos*dy)*self.zoom;
+        (vx + w/2.0 + local_x, vy + h/2.0 + local_y)
+    }
+    // glium viewports are measured from the bottom left of the target
+    pub (super) fn glium_viewport(&self, target_dims : (u32, u32)) -> Option<glium::Rect>
+    {
+        let (x, y, w, h) = self.viewport?;
+        let bottom = target_dims.1 as f64 - (y + h);
+        Some(glium::Rect{left : x.max(0.0) as u32, bottom : bottom.max(0.0) as u32, width : w.max(0.0) as u32, height : h.max(0.0) as u32})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn close(a : (f64, f64), b : (f64, f64)) -> bool
+    {
+        (a.0 - b.0).abs() < 0.0001 && (a.1 - b.1).abs() < 0.0001
+    }
+    
+    #[test]
+    fn test_screen_world_roundtrip()
+    {
+        let mut camera = Camera::new();
+        camera.x = 100.0;
+        camera.y = -40.0;
+        camera.zoom = 2.5;
+        camera.angle = 30.0;
+        camera.viewport = Some((400.0, 0.0, 400.0, 600.0));
+        
+        let dims = (800, 600);
+        let world = camera.screen_to_world(dims, (512.0, 300.0));
+        assert!(close(camera.world_to_screen(dims, world), (512.0, 300.0)));
+        // the camera position is at the center of its viewport
+        assert!(close(camera.world_to_screen(dims, (100.0, -40.0)), (600.0, 300.0)));
+    }
+    
+    #[test]
+    fn test_view_matrix_matches_world_to_screen()
+    {
+        let mut camera = Camera::new();
+        camera.x = 50.0;
+        camera.y = 70.0;
+        camera.zoom = 0.5;
+        camera.angle = -45.0;
+        
+        let dims = (640, 480);
+        let matrix = camera.view_matrix(dims);
+        let (wx, wy) = (123.0f32, 45.0f32);
+        let ndc_x = matrix[0][0]*wx + matrix[1][0]*wy + matrix[3][0];
+        let ndc_y = matrix[0][1]*wx + matrix[1][1]*wy + matrix[3][1];
+        let screen = camera.world_to_screen(dims, (wx as f64, wy as f64));
+        let expected = (screen.0/640.0*2.0 - 1.0, 1.0 - screen.1/480.0*2.0);
+        assert!(close((ndc_x as f64, ndc_y as f64), expected));
+    }
+    
+    #[test]
+    fn test_bounds_clamp()
+    {
+        let mut camera = Camera::new();
+        camera.x = -1000.0;
+        camera.y = 1000.0;
+        camera.bounds = Some((0.0, 0.0, 2000.0, 300.0));
+        // view is 800x600, so it is taller than the bounds and gets centered vertically
+        assert!(close(camera.center((800, 600)), (400.0, 150.0)));
+    }
+}
@@ -0,0 +1,57 @@
+// splits text into runs that each come from one font of a fallback chain, where font 0 is the primary font
+// each char comes from the first font that has a glyph for it, or from the primary font if none do
+// whitespace stays in the run it's in, so that fallback text doesn't break up words or get laid out differently
+pub (super) fn split_font_runs<'a>(text : &'a str, fonts : usize, has_glyph : impl Fn(usize, char) -> bool) -> Vec<(usize, &'a str)>
+{
+    let mut runs : Vec<(usize, &'a str)> = Vec::new();
+    let mut run_start = 0;
+    let mut run_font = None;
+    for (offset, ch) in text.char_indices()
+    {
+        let font = if ch.is_whitespace() || ch.is_control()
+        {
+            run_font.unwrap_or(0)
+        }
+        else
+        {
+            (0..fonts).find(|font| has_glyph(*font, ch)).unwrap_or(0)
+        };
+        match run_font
+        {
+            Some(current) if current != font =>
+            {
+                runs.push((current, &text[run_start..offset]));
+                run_start = offset;
+            }
+            _ => {}
+        }
+        run_font = Some(font);
+    }
+    if let Some(font) = run_font
+    {
+        runs.push((font, &text[run_start..]));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_split_font_runs()
+    {
+        // font 0 only has ascii, font 1 has kana, font 2 has everything
+        let has_glyph = |font : usize, ch : char| match font
+        {
+            0 => ch.is_ascii(),
+            1 => ('\u{3040}'..'\u{30ff}').contains(&ch),
+            _ => true
+        };
+        assert_eq!(split_font_runs("hello there", 3, has_glyph), vec!((0, "hello there")));
+        assert_eq!(split_font_runs("say こんにちは now", 3, has_glyph), vec!((0, "say "), (1, "こんにちは "), (0, "now")));
+        assert_eq!(split_font_runs("ok 日本 ok", 3, has_glyph), vec!((0, "ok "), (2, "日本 "), (0, "ok")));
+        assert_eq!(split_font_runs("日本", 2, has_glyph), vec!((0, "日本")));
+        assert!(split_font_runs("", 3, has_glyph).is_empty());
+    }
+}
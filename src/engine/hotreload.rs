@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// a resource that was loaded from files, along with whatever is needed to load it again
+#[derive(Debug, Clone, PartialEq)]
+pub (super) enum WatchedResource {
+    Sprite{id : u64, fname : String, fit_image : bool},
+    Program{id : u64, vertex : String, fragment : String},
+    Font{id : u64, fname : String},
+}
+
+impl WatchedResource {
+    pub (super) fn kind(&self) -> &'static str
+    {
+        match self
+        {
+            WatchedResource::Sprite{..} => "sprite",
+            WatchedResource::Program{..} => "program",
+            WatchedResource::Font{..} => "font",
+        }
+    }
+    pub (super) fn files(&self) -> String
+    {
+        match self
+        {
+            WatchedResource::Sprite{fname, ..} | WatchedResource::Font{fname, ..} => fname.clone(),
+            WatchedResource::Program{vertex, fragment, ..} => format!("{} + {}", vertex, fragment),
+        }
+    }
+}
+
+// a reload that happened, and why it failed if it did; kept until the script asks for it
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct ReloadEvent {
+    pub (super) kind: &'static str,
+    pub (super) files: String,
+    pub (super) error: Option<String>,
+}
+
+// scripts that never ask for events shouldn't make them pile up forever, so only the latest ones are kept
+const MAX_RELOAD_EVENTS : usize = 64;
+
+struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    resources: Vec<WatchedResource>,
+}
+
+// polls the modification times of every file that resources were loaded from
+// it only checks the disk every `interval` seconds, and only while enabled
+pub (super) struct FileWatcher {
+    pub (super) enabled: bool,
+    pub (super) interval: f64,
+    since_poll: f64,
+    files: Vec<WatchedFile>,
+    events: Vec<ReloadEvent>,
+}
+
+fn modified_time(path : &Path) -> Option<SystemTime>
+{
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+impl FileWatcher {
+    pub (super) fn new() -> FileWatcher
+    {
+        FileWatcher{enabled : false, interval : 0.25, since_poll : 0.0, files : Vec::new(), events : Vec::new()}
+    }
+    pub (super) fn watch(&mut self, path : PathBuf, resource : WatchedResource)
+    {
+        self.watch_with(path, resource, modified_time)
+    }
+    fn watch_with(&mut self, path : PathBuf, resource : WatchedResource, get_modified : impl Fn(&Path) -> Option<SystemTime>)
+    {
+        match self.files.iter_mut().find(|file| file.path == path)
+        {
+            Some(file) =>
+            {
+                if !file.resources.contains(&resource)
+                {
+                    file.resources.push(resource);
+                }
+            }
+            None =>
+            {
+                let modified = get_modified(&path);
+                self.files.push(WatchedFile{path, modified, resources : vec!(resource)});
+            }
+        }
+    }
+    // stops watching for resources that were freed; files without any resources left aren't checked anymore
+    pub (super) fn unwatch(&mut self, matches : impl Fn(&WatchedResource) -> bool)
+    {
+        for file in &mut self.files
+        {
+            file.resources.retain(|resource| !matches(resource));
+        }
+        self.files.retain(|file| !file.resources.is_empty());
+    }
+    pub (super) fn record(&mut self, resource : &WatchedResource, error : Option<String>)
+    {
+        if self.events.len() == MAX_RELOAD_EVENTS
+        {
+            self.events.remove(0);
+        }
+        self.events.push(ReloadEvent{kind : resource.kind(), files : resource.files(), error});
+    }
+    pub (super) fn take_events(&mut self) -> Vec<ReloadEvent>
+    {
+        std::mem::take(&mut self.events)
+    }
+    // returns every resource with a file that changed since the last poll, each resource at most once
+    pub (super) fn poll(&mut self, delta : f64) -> Vec<WatchedResource>
+    {
+        if !self.enabled
+        {
+            return Vec::new();
+        }
+        self.since_poll += delta;
+        if self.since_poll < self.interval
+        {
+            return Vec::new();
+        }
+        self.since_poll = 0.0;
+        self.changed_with(modified_time)
+    }
+    fn changed_with(&mut self, get_modified : impl Fn(&Path) -> Option<SystemTime>) -> Vec<WatchedResource>
+    {
+        let mut changed = Vec::new();
+        for file in &mut self.files
+        {
+            let modified = get_modified(&file.path);
+            // files that are missing (like while an editor is saving them) are checked again on the next poll
+            if modified.is_some() && modified != file.modified
+            {
+                file.modified = modified;
+                for resource in &file.resources
+                {
+                    if !changed.contains(resource)
+                    {
+                        changed.push(resource.clone());
+                    }
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    #[test]
+    fn test_changed_files()
+    {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let later = start + Duration::from_secs(5);
+        let program = WatchedResource::Program{id : 1, vertex : "a.glsl".to_string(), fragment : "b.glsl".to_string()};
+        let sprite = WatchedResource::Sprite{id : 2, fname : "c.png".to_string(), fit_image : true};
+        
+        let mut watcher = FileWatcher::new();
+        watcher.watch_with(PathBuf::from("a.glsl"), program.clone(), |_| Some(start));
+        watcher.watch_with(PathBuf::from("b.glsl"), program.clone(), |_| Some(start));
+        watcher.watch_with(PathBuf::from("c.png"), sprite.clone(), |_| Some(start));
+        
+        assert!(watcher.changed_with(|_| Some(start)).is_empty());
+        // both shaders changing only reloads the program once
+        assert_eq!(watcher.changed_with(|path| if path.ends_with("c.png") { Some(start) } else { Some(later) }), vec!(program.clone()));
+        // missing files are not reported until they come back
+        assert!(watcher.changed_with(|path| if path.ends_with("c.png") { None } else { Some(later) }).is_empty());
+        assert_eq!(watcher.changed_with(|_| Some(later)), vec!(sprite.clone()));
+        
+        watcher.unwatch(|resource| resource == &program);
+        assert_eq!(watcher.files.len(), 1);
+        assert_eq!(watcher.changed_with(|_| Some(later + Duration::from_secs(5))), vec!(sprite.clone()));
+    }
+    
+    #[test]
+    fn test_events()
+    {
+        let program = WatchedResource::Program{id : 1, vertex : "a.glsl".to_string(), fragment : "b.glsl".to_string()};
+        let mut watcher = FileWatcher::new();
+        watcher.record(&program, Some("error: bad shader".to_string()));
+        assert_eq!(watcher.take_events(), vec!(ReloadEvent{kind : "program", files : "a.glsl + b.glsl".to_string(), error : Some("error: bad shader".to_string())}));
+        assert!(watcher.take_events().is_empty());
+        for _ in 0..MAX_RELOAD_EVENTS + 10
+        {
+            watcher.record(&program, None);
+        }
+        assert_eq!(watcher.take_events().len(), MAX_RELOAD_EVENTS);
+    }
+    
+    #[test]
+    fn test_poll_interval()
+    {
+        let mut watcher = FileWatcher::new();
+        assert!(watcher.poll(1.0).is_empty());
+        watcher.enabled = true;
+        watcher.since_poll = 0.0;
+        watcher.poll(0.1);
+        assert!((watcher.since_poll - 0.1).abs() < 0.0001);
+        watcher.poll(0.2);
+        assert_eq!(watcher.since_poll, 0.0);
+    }
+}
@@ -0,0 +1,496 @@
+use std::ops::Range;
+use unicode_bidi::{BidiInfo, Level};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub (super) enum WrapMode {
+    Word,
+    Char,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub (super) enum TextDirection {
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+// how draw_text_ext breaks text into lines and places them
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct TextOptions {
+    pub (super) wrap: WrapMode,
+    pub (super) line_spacing: f32, // multiplier of the font's line height
+    pub (super) max_lines: Option<usize>,
+    pub (super) ellipsis: bool,
+    pub (super) direction: TextDirection,
+}
+
+impl TextOptions {
+    pub (super) fn new() -> TextOptions
+    {
+        TextOptions{wrap : WrapMode::Word, line_spacing : 1.0, max_lines : None, ellipsis : false, direction : TextDirection::Auto}
+    }
+}
+
+pub (super) fn text_wrap_from_name(name : &str) -> Result<WrapMode, String>
+{
+    match name
+    {
+        "word" => Ok(WrapMode::Word),
+        "char" => Ok(WrapMode::Char),
+        "none" => Ok(WrapMode::None),
+        _ => Err(format!("error: unknown text wrap mode `{}`; expected word, char, or none", name))
+    }
+}
+
+pub (super) fn text_direction_from_name(name : &str) -> Result<TextDirection, String>
+{
+    match name
+    {
+        "auto" => Ok(TextDirection::Auto),
+        "ltr" => Ok(TextDirection::Ltr),
+        "rtl" => Ok(TextDirection::Rtl),
+        _ => Err(format!("error: unknown text direction `{}`; expected auto, ltr, or rtl", name))
+    }
+}
+
+// one line of laid out text: the paragraph it's in, and the part of the paragraph it shows, both as byte ranges of the whole text
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct TextLine {
+    pub (super) paragraph: Range<usize>,
+    pub (super) line: Range<usize>,
+    pub (super) ellipsis: bool,
+}
+
+// chinese and japanese text doesn't use spaces, so lines can break before or after any of these
+fn is_cjk(ch : char) -> bool
+{
+    match ch as u32
+    {
+        0x2E80..=0x2FFF | 0x3000..=0x303F | 0x3040..=0x30FF | 0x3100..=0x31FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF | 0x20000..=0x2FFFF => true,
+        _ => false
+    }
+}
+
+// punctuation that shouldn't start a line, even where breaking before a cjk char would otherwise be allowed
+fn is_closing(ch : char) -> bool
+{
+    "、。，．・：；！？）」』】〉》〕ー…‥々ぁぃぅぇぉっゃゅょァィゥェォッャュョ,.!?;:)]}".contains(ch)
+}
+
+// byte offsets where a line is allowed to end, in order; the end of the text is always one of them
+fn break_points(text : &str, wrap : WrapMode) -> Vec<usize>
+{
+    let mut points = Vec::new();
+    let mut prev : Option<char> = None;
+    for (offset, ch) in text.char_indices()
+    {
+        if let Some(prev) = prev
+        {
+            let allowed = match wrap
+            {
+                WrapMode::Char => !is_closing(ch),
+                WrapMode::Word => !ch.is_whitespace() && !is_closing(ch) && (prev.is_whitespace() || is_cjk(prev) || is_cjk(ch) || (prev == '-' && ch.is_alphanumeric())),
+                WrapMode::None => false
+            };
+            if allowed
+            {
+                points.push(offset);
+            }
+        }
+        prev = Some(ch);
+    }
+    points.push(text.len());
+    points
+}
+
+// greedily breaks one paragraph into lines no wider than max_width; lines don't include the whitespace they were broken at
+// words that don't fit on a line of their own are broken between chars, and every line gets at least one char
+// widths are assumed to grow with the length of the text, so the longest fitting line can be found by bisection
+pub (super) fn break_paragraph(text : &str, max_width : f32, wrap : WrapMode, measure : &impl Fn(&str) -> f32) -> Vec<Range<usize>>
+{
+    let trimmed_end = |start : usize, end : usize| start + text[start..end].trim_end().len();
+    if wrap == WrapMode::None
+    {
+        return vec!(0..trimmed_end(0, text.len()));
+    }
+    let points = break_points(text, wrap);
+    let mut lines = Vec::new();
+    let mut start = text.len() - text.trim_start().len();
+    while start < text.len()
+    {
+        let fits = |end : &usize| measure(&text[start..trimmed_end(start, *end)]) <= max_width;
+        let candidates = &points[points.partition_point(|point| *point <= start)..];
+        let count = candidates.partition_point(fits);
+        let end = if count > 0
+        {
+            candidates[count - 1]
+        }
+        else
+        {
+            let limit = candidates[0];
+            let chars : Vec<usize> = text[start..limit].char_indices().skip(1).map(|(offset, _)| start + offset).chain(std::iter::once(limit)).collect();
+            chars[chars.partition_point(fits).max(1) - 1]
+        };
+        lines.push(start..trimmed_end(start, end));
+        start = end + (text[end..].len() - text[end..].trim_start().len());
+    }
+    if lines.is_empty()
+    {
+        lines.push(0..0);
+    }
+    lines
+}
+
+// the end of the longest start of the text that still fits once an ellipsis is put after it
+pub (super) fn ellipsis_end(text : &str, max_width : f32, measure : &impl Fn(&str) -> f32) -> usize
+{
+    let ends : Vec<usize> = text.char_indices().skip(1).map(|(offset, _)| offset).chain(std::iter::once(text.len())).collect();
+    let count = ends.partition_point(|end| measure(&format!("{}…", text[..*end].trim_end())) <= max_width);
+    if count == 0
+    {
+        return 0;
+    }
+    text[..ends[count - 1]].trim_end().len()
+}
+
+// breaks text into lines, with each \n starting a new paragraph, and keeps at most max_lines of them
+// with ellipsis, lines that are cut off or that don't fit (because they weren't wrapped) end in an ellipsis instead
+pub (super) fn layout_lines(text : &str, max_width : f32, max_lines : Option<usize>, wrap : WrapMode, ellipsis : bool, measure : &impl Fn(&str) -> f32) -> Vec<TextLine>
+{
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for paragraph in text.split('\n')
+    {
+        let paragraph = paragraph.trim_end_matches('\r');
+        let range = start..start + paragraph.len();
+        for line in break_paragraph(paragraph, max_width, wrap, measure)
+        {
+            lines.push(TextLine{paragraph : range.clone(), line : start + line.start..start + line.end, ellipsis : false});
+        }
+        start = range.end + text[range.end..].find('\n').map(|offset| offset + 1).unwrap_or(0);
+    }
+    if let Some(max_lines) = max_lines
+    {
+        if lines.len() > max_lines
+        {
+            lines.truncate(max_lines);
+            if let Some(last) = lines.last_mut()
+            {
+                last.ellipsis = ellipsis;
+            }
+        }
+    }
+    if ellipsis
+    {
+        for line in &mut lines
+        {
+            if line.ellipsis || measure(&text[line.line.clone()]) > max_width
+            {
+                line.ellipsis = true;
+                line.line.end = line.line.start + ellipsis_end(&text[line.line.clone()], max_width, measure);
+            }
+        }
+    }
+    lines
+}
+
+// the first of an arabic letter's presentation forms (isolated, final, then initial and medial for letters that join on both sides), and whether it joins to the letter after it
+fn arabic_forms(ch : char) -> Option<(u32, bool)>
+{
+    let forms = match ch as u32
+    {
+        0x0622 => (0xFE81, false),
+        0x0623 => (0xFE83, false),
+        0x0624 => (0xFE85, false),
+        0x0625 => (0xFE87, false),
+        0x0626 => (0xFE89, true),
+        0x0627 => (0xFE8D, false),
+        0x0628 => (0xFE8F, true),
+        0x0629 => (0xFE93, false),
+        0x062A => (0xFE95, true),
+        0x062B => (0xFE99, true),
+        0x062C => (0xFE9D, true),
+        0x062D => (0xFEA1, true),
+        0x062E => (0xFEA5, true),
+        0x062F => (0xFEA9, false),
+        0x0630 => (0xFEAB, false),
+        0x0631 => (0xFEAD, false),
+        0x0632 => (0xFEAF, false),
+        0x0633 => (0xFEB1, true),
+        0x0634 => (0xFEB5, true),
+        0x0635 => (0xFEB9, true),
+        0x0636 => (0xFEBD, true),
+        0x0637 => (0xFEC1, true),
+        0x0638 => (0xFEC5, true),
+        0x0639 => (0xFEC9, true),
+        0x063A => (0xFECD, true),
+        0x0641 => (0xFED1, true),
+        0x0642 => (0xFED5, true),
+        0x0643 => (0xFED9, true),
+        0x0644 => (0xFEDD, true),
+        0x0645 => (0xFEE1, true),
+        0x0646 => (0xFEE5, true),
+        0x0647 => (0xFEE9, true),
+        0x0648 => (0xFEED, false),
+        0x0649 => (0xFEEF, false),
+        0x064A => (0xFEF1, true),
+        // persian letters
+        0x067E => (0xFB56, true),
+        0x0686 => (0xFB7A, true),
+        0x0698 => (0xFB8A, false),
+        0x06A9 => (0xFB8E, true),
+        0x06AF => (0xFB92, true),
+        0x06CC => (0xFBFC, true),
+        _ => return None
+    };
+    Some(forms)
+}
+
+// lam followed by one of these alefs becomes a single ligature; the isolated form comes first, then the final one
+fn lam_alef(alef : char) -> Option<u32>
+{
+    match alef as u32
+    {
+        0x0622 => Some(0xFEF5),
+        0x0623 => Some(0xFEF7),
+        0x0625 => Some(0xFEF9),
+        0x0627 => Some(0xFEFB),
+        _ => None
+    }
+}
+
+// vowel marks sit on top of letters without breaking the join between them
+fn is_transparent(ch : char) -> bool
+{
+    match ch as u32
+    {
+        0x0610..=0x061A | 0x064B..=0x065F | 0x0670 | 0x06D6..=0x06DC | 0x06DF..=0x06E4 | 0x06E7 | 0x06E8 | 0x06EA..=0x06ED => true,
+        _ => false
+    }
+}
+
+const TATWEEL : char = '\u{0640}';
+
+fn joins_next(ch : char) -> bool
+{
+    ch == TATWEEL || arabic_forms(ch).map(|(_, dual)| dual).unwrap_or(false)
+}
+
+fn joins_previous(ch : char) -> bool
+{
+    ch == TATWEEL || arabic_forms(ch).is_some()
+}
+
+// replaces arabic letters with the presentation forms that connect them to their neighbors, since fonts only draw them one char at a time
+// this goes over the text in logical order, so it has to happen before the text is reordered for drawing
+pub (super) fn shape_arabic(text : &str) -> String
+{
+    let chars : Vec<char> = text.chars().collect();
+    let neighbor = |indices : &mut dyn Iterator<Item = usize>| indices.map(|index| chars[index]).find(|ch| !is_transparent(*ch));
+    let mut shaped = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len()
+    {
+        let ch = chars[index];
+        let forms = match arabic_forms(ch)
+        {
+            Some(forms) => forms,
+            None =>
+            {
+                shaped.push(ch);
+                index += 1;
+                continue;
+            }
+        };
+        let joined_before = neighbor(&mut (0..index).rev()).map(joins_next).unwrap_or(false);
+        if let Some(ligature) = chars.get(index + 1).filter(|_| ch == '\u{0644}').and_then(|alef| lam_alef(*alef))
+        {
+            shaped.push(std::char::from_u32(ligature + joined_before as u32).unwrap());
+            index += 2;
+            continue;
+        }
+        let joined_after = forms.1 && neighbor(&mut (index + 1..chars.len())).map(joins_previous).unwrap_or(false);
+        let form = match (joined_before, joined_after)
+        {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        shaped.push(std::char::from_u32(forms.0 + form).unwrap());
+        index += 1;
+    }
+    shaped
+}
+
+// widths at least this big are what text functions get when there's no box to wrap or align in
+fn is_unbounded(width : f32) -> bool
+{
+    width >= 999999999.0
+}
+
+// a laid out line as it gets drawn: shaped and in visual order, with its left edge relative to the text's position, and its width
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct PlacedLine {
+    pub (super) text: String,
+    pub (super) left: f32,
+    pub (super) width: f32,
+}
+
+// shapes and reorders laid out lines for drawing, then aligns them; halign is 0 for left, 0.5 for center, and 1 for right alignment
+// right-to-left paragraphs start from the other side of the box, and when there's no box, the widest line is used as one
+pub (super) fn place_lines(text : &str, lines : &[TextLine], direction : TextDirection, max_width : f32, halign : f32, measure : &impl Fn(&str) -> f32) -> Vec<PlacedLine>
+{
+    let default_level = match direction
+    {
+        TextDirection::Auto => None,
+        TextDirection::Ltr => Some(Level::ltr()),
+        TextDirection::Rtl => Some(Level::rtl()),
+    };
+    let mut paragraph_level : Option<(usize, Option<Level>)> = None;
+    let mut shown = Vec::new();
+    for line in lines
+    {
+        if paragraph_level.map(|(start, _)| start) != Some(line.paragraph.start)
+        {
+            let info = BidiInfo::new(&text[line.paragraph.clone()], default_level);
+            paragraph_level = Some((line.paragraph.start, info.paragraphs.first().map(|paragraph| paragraph.level)));
+        }
+        let level = paragraph_level.unwrap().1;
+        let rtl = level.map(|level| level.is_rtl()).unwrap_or(direction == TextDirection::Rtl);
+        let shaped = shape_arabic(&text[line.line.clone()]);
+        // the line is reordered on its own, but at the level of the whole paragraph it's in
+        let info = BidiInfo::new(&shaped, level.or(default_level));
+        let mut ordered = match info.paragraphs.first()
+        {
+            Some(paragraph) if !shaped.is_empty() => info.reorder_line(paragraph, 0..shaped.len()).into_owned(),
+            _ => shaped.clone()
+        };
+        if line.ellipsis
+        {
+            if rtl { ordered.insert(0, '…') } else { ordered.push('…') }
+        }
+        let width = measure(&ordered);
+        shown.push((ordered, rtl, width));
+    }
+    
+    let box_width = if is_unbounded(max_width) { shown.iter().map(|line| line.2).fold(0.0, f32::max) } else { max_width };
+    shown.into_iter().map(|(text, rtl, width)|
+    {
+        let left = if rtl { (box_width - width)*(1.0 - halign) - box_width*halign } else { -width*halign };
+        PlacedLine{text, left, width}
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // every char is 10 wide
+    fn measure(text : &str) -> f32
+    {
+        text.chars().count() as f32*10.0
+    }
+    
+    fn line_texts<'a>(text : &'a str, lines : &[TextLine]) -> Vec<&'a str>
+    {
+        lines.iter().map(|line| &text[line.line.clone()]).collect()
+    }
+    
+    #[test]
+    fn test_word_wrap()
+    {
+        let text = "the quick brown fox";
+        let lines = layout_lines(text, 100.0, None, WrapMode::Word, false, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("the quick", "brown fox"));
+        // words too long for a line are broken between chars
+        let text = "a verylongword";
+        let lines = layout_lines(text, 50.0, None, WrapMode::Word, false, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("a", "veryl", "ongwo", "rd"));
+        let text = "well-known";
+        assert_eq!(line_texts(text, &layout_lines(text, 60.0, None, WrapMode::Word, false, &measure)), vec!("well-", "known"));
+    }
+    
+    #[test]
+    fn test_cjk_and_char_wrap()
+    {
+        let text = "日本語の文章です。";
+        let lines = layout_lines(text, 40.0, None, WrapMode::Word, false, &measure);
+        // the full stop isn't allowed to start a line
+        assert_eq!(line_texts(text, &lines), vec!("日本語の", "文章で", "す。"));
+        let text = "abc def";
+        let lines = layout_lines(text, 20.0, None, WrapMode::Char, false, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("ab", "c", "de", "f"));
+    }
+    
+    #[test]
+    fn test_paragraphs()
+    {
+        let text = "one\r\n\ntwo three";
+        let lines = layout_lines(text, 50.0, None, WrapMode::Word, false, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("one", "", "two", "three"));
+        assert_eq!(lines[0].paragraph, 0..3);
+        assert_eq!(lines[3].paragraph, 6..15);
+    }
+    
+    #[test]
+    fn test_ellipsis_and_max_lines()
+    {
+        let text = "a long line of text";
+        let lines = layout_lines(text, 80.0, None, WrapMode::None, true, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("a long"));
+        assert!(lines[0].ellipsis);
+        let lines = layout_lines(text, 80.0, Some(2), WrapMode::Word, true, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("a long", "line of"));
+        assert!(!lines[0].ellipsis && lines[1].ellipsis);
+        let lines = layout_lines(text, 80.0, Some(2), WrapMode::Word, false, &measure);
+        assert_eq!(line_texts(text, &lines), vec!("a long", "line of"));
+        assert!(!lines[1].ellipsis);
+        assert_eq!(ellipsis_end("abc", 5.0, &measure), 0);
+    }
+    
+    #[test]
+    fn test_shape_arabic()
+    {
+        // seen, lam, alef, meem: seen starts the word, lam and alef join into one ligature, and meem can't join to alef so it stands alone
+        assert_eq!(shape_arabic("سلام"), "\u{FEB3}\u{FEFC}\u{FEE1}");
+        // beh, then beh again with a vowel mark between them that doesn't break the join
+        assert_eq!(shape_arabic("ب\u{064E}ب"), "\u{FE91}\u{064E}\u{FE90}");
+        assert_eq!(shape_arabic("ب ب"), "\u{FE8F} \u{FE8F}");
+        assert_eq!(shape_arabic("abc"), "abc");
+    }
+    
+    #[test]
+    fn test_place_mixed_lines()
+    {
+        // a left to right paragraph with a hebrew word in it, and a right to left one with an english word in it
+        let text = "abc אבג\nאבג abc";
+        let lines = layout_lines(text, 100.0, None, WrapMode::Word, false, &measure);
+        let placed = place_lines(text, &lines, TextDirection::Auto, 100.0, 0.0, &measure);
+        assert_eq!(placed[0], PlacedLine{text : "abc גבא".to_string(), left : 0.0, width : 70.0});
+        assert_eq!(placed[1], PlacedLine{text : "abc גבא".to_string(), left : 30.0, width : 70.0});
+        // forcing a direction changes which side the paragraph starts from and where the words go
+        let placed = place_lines(text, &lines, TextDirection::Ltr, 100.0, 0.0, &measure);
+        assert_eq!((placed[1].text.as_str(), placed[1].left), ("גבא abc", 0.0));
+        let placed = place_lines(text, &lines, TextDirection::Rtl, 100.0, 0.0, &measure);
+        assert_eq!((placed[0].text.as_str(), placed[0].left), ("גבא abc", 30.0));
+    }
+    
+    #[test]
+    fn test_place_rtl_alignment()
+    {
+        let text = "אבג דה\nו";
+        let lines = layout_lines(text, 100.0, None, WrapMode::Word, false, &measure);
+        let lefts = |max_width, halign| place_lines(text, &lines, TextDirection::Auto, max_width, halign, &measure).iter().map(|line| line.left).collect::<Vec<f32>>();
+        assert_eq!(lefts(100.0, 0.0), vec!(40.0, 90.0));
+        assert_eq!(lefts(100.0, 0.5), vec!(-30.0, -5.0));
+        assert_eq!(lefts(100.0, 1.0), vec!(-100.0, -100.0));
+        // with no box, the lines are aligned against the widest of them instead of against a huge width
+        assert_eq!(lefts(999999999.0, 0.0), vec!(0.0, 50.0));
+        assert_eq!(lefts(999999999.0, 1.0), vec!(-60.0, -60.0));
+        // the ellipsis goes at the end of the line, which is on the left for right-to-left text
+        let lines = layout_lines(text, 40.0, None, WrapMode::None, true, &measure);
+        assert_eq!(place_lines(text, &lines, TextDirection::Auto, 40.0, 0.0, &measure)[0].text, "…גבא");
+    }
+}
@@ -0,0 +1,228 @@
+// a run of text that shares one style; unset fields fall back to whatever the text is drawn with
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct MarkupSpan {
+    pub (super) text: String,
+    pub (super) color: Option<[f32; 4]>,
+    pub (super) size: Option<f32>,
+    pub (super) font: Option<String>,
+    pub (super) bold: bool,
+    pub (super) italic: bool,
+    pub (super) wave: bool,
+    pub (super) shake: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MarkupStyle {
+    colors: Vec<[f32; 4]>,
+    sizes: Vec<f32>,
+    fonts: Vec<String>,
+    bold: u32,
+    italic: u32,
+    wave: u32,
+    shake: u32,
+}
+
+impl MarkupSpan {
+    pub (super) fn plain(text : &str) -> MarkupSpan
+    {
+        MarkupStyle::default().span(text.to_string())
+    }
+    // colors in markup replace the color the text is drawn with, but are still faded by its alpha
+    pub (super) fn color_over(&self, base : [f32; 4]) -> [f32; 4]
+    {
+        match self.color
+        {
+            Some(mut color) =>
+            {
+                color[3] *= base[3];
+                color
+            }
+            None => base
+        }
+    }
+}
+
+impl MarkupStyle {
+    fn span(&self, text : String) -> MarkupSpan
+    {
+        MarkupSpan {
+            text,
+            color : self.colors.last().cloned(),
+            size : self.sizes.last().cloned(),
+            font : self.fonts.last().cloned(),
+            bold : self.bold > 0,
+            italic : self.italic > 0,
+            wave : self.wave > 0,
+            shake : self.shake > 0,
+        }
+    }
+    // returns false for tags that aren't known or can't be applied, which are then kept as plain text
+    fn apply(&mut self, tag : &str) -> bool
+    {
+        fn pop<T>(stack : &mut Vec<T>) -> bool
+        {
+            stack.pop().is_some()
+        }
+        fn unnest(depth : &mut u32) -> bool
+        {
+            if *depth == 0
+            {
+                return false;
+            }
+            *depth -= 1;
+            true
+        }
+        let (name, value) = match tag.find('=')
+        {
+            Some(split) => (&tag[..split], Some(tag[split + 1..].trim())),
+            None => (tag, None)
+        };
+        match (name.trim(), value)
+        {
+            ("b", None) => { self.bold += 1; true }
+            ("i", None) => { self.italic += 1; true }
+            ("/b", None) => unnest(&mut self.bold),
+            ("/i", None) => unnest(&mut self.italic),
+            ("wave", None) => { self.wave += 1; true }
+            ("shake", None) => { self.shake += 1; true }
+            ("/wave", None) => unnest(&mut self.wave),
+            ("/shake", None) => unnest(&mut self.shake),
+            ("color", Some(value)) => match parse_hex_color(value)
+            {
+                Some(color) => { self.colors.push(color); true }
+                None => false
+            }
+            ("size", Some(value)) => match value.parse::<f32>()
+            {
+                Ok(size) if size > 0.0 => { self.sizes.push(size); true }
+                _ => false
+            }
+            ("font", Some(value)) if !value.is_empty() => { self.fonts.push(value.to_string()); true }
+            ("/color", None) => pop(&mut self.colors),
+            ("/size", None) => pop(&mut self.sizes),
+            ("/font", None) => pop(&mut self.fonts),
+            _ => false
+        }
+    }
+}
+
+// #rgb, #rrggbb, or #rrggbbaa
+pub (super) fn parse_hex_color(text : &str) -> Option<[f32; 4]>
+{
+    let hex = text.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    let digits : Vec<u32> = hex.chars().map(|c| c.to_digit(16).unwrap()).collect();
+    let channels : Vec<u32> = match digits.len()
+    {
+        3 => digits.iter().map(|x| x*17).collect(),
+        6 | 8 => digits.chunks(2).map(|pair| pair[0]*16 + pair[1]).collect(),
+        _ => return None
+    };
+    let alpha = channels.get(3).cloned().unwrap_or(255);
+    Some([channels[0] as f32/255.0, channels[1] as f32/255.0, channels[2] as f32/255.0, alpha as f32/255.0])
+}
+
+// parses bbcode-like markup: [b], [i], [wave], [shake], [color=#rrggbb], [size=32], and [font=Name], each closed by [/tag]
+// `[[` is a literal `[`, and anything in brackets that isn't a known tag is left in the text as-is
+pub (super) fn parse_markup(text : &str) -> Vec<MarkupSpan>
+{
+    let mut spans : Vec<MarkupSpan> = Vec::new();
+    let mut style = MarkupStyle::default();
+    let mut current = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('[')
+    {
+        current.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if rest.starts_with("[[")
+        {
+            current.push('[');
+            rest = &rest[2..];
+            continue;
+        }
+        let close = match rest.find(']')
+        {
+            Some(close) => close,
+            None => break
+        };
+        let mut next_style = style.clone();
+        if next_style.apply(&rest[1..close])
+        {
+            if !current.is_empty()
+            {
+                spans.push(style.span(std::mem::replace(&mut current, String::new())));
+            }
+            style = next_style;
+        }
+        else
+        {
+            current.push_str(&rest[..close + 1]);
+        }
+        rest = &rest[close + 1..];
+    }
+    current.push_str(rest);
+    if !current.is_empty()
+    {
+        spans.push(style.span(current));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_nested_tags()
+    {
+        let spans = parse_markup("Hello [b]bold [i]both[/i][/b] [color=#f00]red[/color] done");
+        let texts : Vec<&str> = spans.iter().map(|span| span.text.as_str()).collect();
+        assert_eq!(texts, vec!("Hello ", "bold ", "both", " ", "red", " done"));
+        assert!(spans[1].bold && !spans[1].italic);
+        assert!(spans[2].bold && spans[2].italic);
+        assert!(!spans[3].bold);
+        assert_eq!(spans[4].color, Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(spans[5].color, None);
+    }
+    
+    #[test]
+    fn test_size_and_font()
+    {
+        let spans = parse_markup("[size=32][font=Mono]code[/font] big[/size]");
+        assert_eq!(spans[0].size, Some(32.0));
+        assert_eq!(spans[0].font, Some("Mono".to_string()));
+        assert_eq!(spans[1].text, " big");
+        assert_eq!(spans[1].font, None);
+    }
+    
+    #[test]
+    fn test_effects()
+    {
+        let spans = parse_markup("[wave]hi [shake]there[/shake][/wave][/wave]");
+        assert!(spans[0].wave && !spans[0].shake);
+        assert!(spans[1].wave && spans[1].shake);
+        // the unmatched closing tag stays in the text
+        assert_eq!(spans[2].text, "[/wave]");
+        assert!(!spans[2].wave);
+    }
+    
+    #[test]
+    fn test_literal_brackets()
+    {
+        let spans = parse_markup("a [[b] [unknown] [/b] [size=big] [");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "a [b] [unknown] [/b] [size=big] [");
+    }
+    
+    #[test]
+    fn test_hex_colors()
+    {
+        assert_eq!(parse_hex_color("#fff"), Some([1.0, 1.0, 1.0, 1.0]));
+        assert_eq!(parse_hex_color("#00ff0080"), Some([0.0, 1.0, 0.0, 128.0/255.0]));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("red"), None);
+    }
+}
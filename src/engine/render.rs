@@ -1,7 +1,9 @@
 use glium::{implement_vertex, uniform};
 use glium::texture::{SrgbTexture2d, DepthTexture2d};
-use glium::uniforms::{Sampler, MinifySamplerFilter, MagnifySamplerFilter};
+use glium::uniforms::{Sampler, MinifySamplerFilter, MagnifySamplerFilter, SamplerWrapFunction, Uniforms, UniformValue};
+use glium::texture::MipmapsOption;
 use std::rc::Rc;
+use std::cell::Cell;
 
 use glium::Surface as _;
 
@@ -25,63 +27,448 @@ fn m4mult(a : &[[f32; 4]; 4], b : &[[f32; 4]; 4]) -> [[f32; 4]; 4]
 
 fn deg2rad(x : f64) -> f64
 {
-    x * std::f64::consts::PI / 360.0
+    x * std::f64::consts::PI / 180.0
 }
 
 #[derive(Debug, Copy, Clone)]
 pub (super) struct Vertex {
-    position: [f32; 2]
+    position: [f32; 2],
+    vertex_color: [f32; 4],
+    vertex_texcoord: [f32; 2],
 }
 
-implement_vertex!(Vertex, position);
+implement_vertex!(Vertex, position, vertex_color, vertex_texcoord);
 
-#[derive(Debug)]
+impl Vertex {
+    // the texture coordinate follows the position, which is what the unit quad that sprites are drawn with needs
+    pub (super) fn new(x : f32, y : f32, color : [f32; 4]) -> Vertex
+    {
+        Vertex{position : [x, y], vertex_color : color, vertex_texcoord : [x, y]}
+    }
+    pub (super) fn textured(position : [f32; 2], texcoord : [f32; 2], color : [f32; 4]) -> Vertex
+    {
+        Vertex{position, vertex_color : color, vertex_texcoord : texcoord}
+    }
+}
+
+// text outlines and shadows are drawn with the built-in outline program, which needs to know where each glyph's part of the texture ends
+#[derive(Debug, Copy, Clone)]
+pub (super) struct OutlineVertex {
+    position: [f32; 2],
+    vertex_color: [f32; 4],
+    vertex_texcoord: [f32; 2],
+    tex_rect: [f32; 4],
+    radius: f32, // in texels
+}
+
+implement_vertex!(OutlineVertex, position, vertex_color, vertex_texcoord, tex_rect, radius);
+
+impl OutlineVertex {
+    // one copy of a glyph, grown by its radius in pixels, which covers texel_radius texels of the glyph's texture
+    fn push_glyph(vertices : &mut Vec<OutlineVertex>, min : (f32, f32), max : (f32, f32), tex_min : (f32, f32), tex_max : (f32, f32), radius : f32, texel_radius : f32, color : [f32; 4])
+    {
+        let (draw, tex) = grown_quad(min, max, tex_min, tex_max, radius);
+        let tex_rect = [tex_min.0, tex_min.1, tex_max.0, tex_max.1];
+        let corners = quad_corners(draw.0, draw.1, tex.0, tex.1);
+        vertices.extend(corners.iter().map(|(position, texcoord)| OutlineVertex{position : *position, vertex_color : color, vertex_texcoord : *texcoord, tex_rect, radius : texel_radius}));
+    }
+}
+
+// vertex buffers that get reused only ever grow, so that drawing lots of small batches doesn't allocate every time
+fn write_vertices<T : glium::Vertex + Copy>(buffer : &mut glium::VertexBuffer<T>, facade : &impl Facade, vertices : &[T])
+{
+    if buffer.len() < vertices.len()
+    {
+        *buffer = glium::VertexBuffer::empty_dynamic(facade, vertices.len().next_power_of_two()).unwrap();
+    }
+    buffer.slice(0..vertices.len()).unwrap().write(vertices);
+}
+
+#[derive(Debug, Clone)]
 pub (super) struct SpriteImage {
-    origin: (f64, f64),
-    topleft: (f64, f64),
-    bottomright: (f64, f64),
+    pub (super) origin: (f64, f64),
+    pub (super) topleft: (f64, f64),
+    pub (super) bottomright: (f64, f64),
+    pub (super) duration: Option<f64>, // seconds, only known for images imported from sprite sheet metadata
 }
 
 impl SpriteImage {
     pub (super) fn basic(origin: (f64, f64), tex : &SrgbTexture2d) -> SpriteImage
     {
-        SpriteImage{origin, topleft: (0.0, 0.0), bottomright: (tex.width() as f64, tex.height() as f64)}
+        SpriteImage{origin, topleft: (0.0, 0.0), bottomright: (tex.width() as f64, tex.height() as f64), duration: None}
     }
     pub (super) fn extended(origin: (f64, f64), topleft: (f64, f64), bottomright : (f64, f64)) -> SpriteImage
     {
-        SpriteImage{origin, topleft, bottomright}
+        SpriteImage{origin, topleft, bottomright, duration: None}
+    }
+    pub (super) fn with_duration(mut self, duration : Option<f64>) -> SpriteImage
+    {
+        self.duration = duration;
+        self
     }
 }
 
+// a named range of images, like an aseprite frame tag
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct SpriteTag {
+    pub (super) name: String,
+    pub (super) from: usize,
+    pub (super) to: usize,
+    pub (super) direction: String,
+}
+
 #[derive(Debug)]
 pub (super) struct SpriteSheet {
     images: Vec<SpriteImage>,
     texture: SrgbTexture2d,
+    tags: Vec<SpriteTag>,
+    animations: HashMap<String, Animation>,
+    pub (super) sampler: SamplerSettings,
+}
+
+impl SpriteSheet {
+    pub (super) fn new(texture : SrgbTexture2d, images : Vec<SpriteImage>) -> SpriteSheet
+    {
+        SpriteSheet{images, texture, tags: Vec::new(), animations: HashMap::new(), sampler: SamplerSettings::new()}
+    }
+    pub (super) fn images(&self) -> &Vec<SpriteImage>
+    {
+        &self.images
+    }
+    pub (super) fn tags(&self) -> &Vec<SpriteTag>
+    {
+        &self.tags
+    }
+    pub (super) fn animations(&self) -> &HashMap<String, Animation>
+    {
+        &self.animations
+    }
 }
 
 pub (super) struct Surface {
-    dims : (u32, u32),
+    pub (super) dims : (u32, u32),
     rgba : SrgbTexture2d,
     depth : DepthTexture2d,
+    pub (super) sampler : SamplerSettings,
+    mipmaps_stale : Cell<bool>, // once rgba has room for mipmaps, they get regenerated on the gpu the next time they're needed after the surface is drawn to
 }
 
 impl Surface {
-    pub (crate) fn new(display : &glium::Display, (w, h) : (u32, u32)) -> Surface
+    pub (crate) fn new(display : &Backend, (w, h) : (u32, u32)) -> Surface
     {
-        let rgba = SrgbTexture2d::empty(display, w, h).unwrap();
+        let rgba = SrgbTexture2d::empty_with_mipmaps(display, MipmapsOption::NoMipmap, w, h).unwrap();
         let depth = DepthTexture2d::empty(display, w, h).unwrap();
         let mut target = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(display, &rgba, &depth).unwrap();
         target.clear_color_srgb_and_depth((0.0, 0.0, 0.0, 0.0), 1.0);
-        Surface{dims : (w, h), rgba, depth}
+        Surface{dims : (w, h), rgba, depth, sampler : SamplerSettings::new(), mipmaps_stale : Cell::new(true)}
     }
-    pub (crate) fn as_framebuffer<'a>(&'a self, display : &glium::Display) -> glium::framebuffer::SimpleFrameBuffer<'a>
+    pub (crate) fn as_framebuffer<'a>(&'a self, display : &Backend) -> glium::framebuffer::SimpleFrameBuffer<'a>
     {
         glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(display, &self.rgba, &self.depth).unwrap()
     }
-    fn clear_color_infinite_depth(&self, display : &glium::Display, color : (f32, f32, f32, f32))
+    fn clear_color_infinite_depth(&self, display : &Backend, color : (f32, f32, f32, f32))
     {
         self.as_framebuffer(display).clear_color_srgb_and_depth(color, 1.0);
     }
+    // surfaces start out without room for mipmaps, since most are never drawn smaller; turning them on moves what's on the surface to a texture that has room
+    pub (super) fn set_mipmaps(&mut self, display : &Backend, mipmaps : bool)
+    {
+        self.sampler.mipmaps = mipmaps;
+        if mipmaps && self.rgba.get_mipmap_levels() == 1
+        {
+            let rgba = SrgbTexture2d::empty_with_mipmaps(display, MipmapsOption::EmptyMipmaps, self.dims.0, self.dims.1).unwrap();
+            let target = glium::framebuffer::SimpleFrameBuffer::new(display, &rgba).unwrap();
+            glium::framebuffer::SimpleFrameBuffer::new(display, &self.rgba).unwrap().fill(&target, MagnifySamplerFilter::Nearest);
+            drop(target);
+            self.rgba = rgba;
+            self.mipmaps_stale.set(true);
+        }
+    }
+    // the surface's texture, with its mipmaps brought up to date first if its sampler uses them
+    fn texture(&self) -> &SrgbTexture2d
+    {
+        if self.sampler.mipmaps && self.mipmaps_stale.get()
+        {
+            self.rgba.generate_mipmaps();
+            self.mipmaps_stale.set(false);
+        }
+        &self.rgba
+    }
+    // rows are returned from top to bottom, four bytes per pixel
+    pub (crate) fn read_rgba(&self) -> Vec<u8>
+    {
+        let image : glium::texture::RawImage2d<u8> = self.rgba.read();
+        let row_len = image.width as usize * 4;
+        let mut rows = image.data.chunks(row_len).map(|row| row.to_vec()).collect::<Vec<_>>();
+        rows.reverse();
+        rows.concat()
+    }
+}
+
+// draw state set by scripts; it persists across frames until it is reset
+#[derive(Debug, Clone)]
+pub (super) struct DrawState {
+    pub (super) blend : glium::Blend,
+    pub (super) depth_test : glium::DepthTest,
+    pub (super) depth_write : bool,
+    pub (super) depth_value : f32,
+    pub (super) scissor : Option<(f64, f64, f64, f64)>, // x, y, w, h from the top left of the target
+    pub (super) color_mask : (bool, bool, bool, bool),
+    pub (super) color : [f32; 4], // used by text drawn without its own color
+    pub (super) text_size : f32,
+    pub (super) halign : glyph_brush::HorizontalAlign,
+    pub (super) valign : glyph_brush::VerticalAlign,
+    pub (super) text_effects : TextEffects,
+    pub (super) text_decoration : TextDecoration,
+}
+
+impl DrawState {
+    pub (super) fn new() -> DrawState
+    {
+        DrawState
+        {
+            blend : glium::Blend::alpha_blending(),
+            depth_test : glium::DepthTest::Overwrite,
+            depth_write : false,
+            depth_value : 0.0,
+            scissor : None,
+            color_mask : (true, true, true, true),
+            color : [1.0, 1.0, 1.0, 1.0],
+            text_size : 24.0,
+            halign : glyph_brush::HorizontalAlign::Left,
+            valign : glyph_brush::VerticalAlign::Top,
+            text_effects : TextEffects::new(),
+            text_decoration : TextDecoration::new(),
+        }
+    }
+}
+
+pub (super) fn blend_mode_from_name(name : &str) -> Result<glium::Blend, String>
+{
+    use glium::{Blend, BlendingFunction::*, LinearBlendingFactor::*};
+    let keep_alpha = Addition{source : Zero, destination : One};
+    match name
+    {
+        "alpha" => Ok(Blend::alpha_blending()),
+        "additive" => Ok(Blend{color : Addition{source : SourceAlpha, destination : One}, alpha : keep_alpha, ..Default::default()}),
+        "multiply" => Ok(Blend{color : Addition{source : DestinationColor, destination : Zero}, alpha : keep_alpha, ..Default::default()}),
+        "premultiplied" => Ok(Blend{color : Addition{source : One, destination : OneMinusSourceAlpha}, alpha : Addition{source : One, destination : OneMinusSourceAlpha}, ..Default::default()}),
+        "subtract" => Ok(Blend{color : ReverseSubtraction{source : SourceAlpha, destination : One}, alpha : keep_alpha, ..Default::default()}),
+        "replace" => Ok(Blend{color : AlwaysReplace, alpha : AlwaysReplace, ..Default::default()}),
+        _ => Err(format!("error: unknown blend mode `{}`", name))
+    }
+}
+
+pub (super) fn halign_from_name(name : &str) -> Result<glyph_brush::HorizontalAlign, String>
+{
+    use glyph_brush::HorizontalAlign::*;
+    match name
+    {
+        "left" => Ok(Left),
+        "center" => Ok(Center),
+        "right" => Ok(Right),
+        _ => Err(format!("error: unknown horizontal alignment `{}`; expected left, center, or right", name))
+    }
+}
+
+pub (super) fn valign_from_name(name : &str) -> Result<glyph_brush::VerticalAlign, String>
+{
+    use glyph_brush::VerticalAlign::*;
+    match name
+    {
+        "top" => Ok(Top),
+        "center" => Ok(Center),
+        "bottom" => Ok(Bottom),
+        _ => Err(format!("error: unknown vertical alignment `{}`; expected top, center, or bottom", name))
+    }
+}
+
+pub (super) fn blend_factor_from_name(name : &str) -> Result<glium::LinearBlendingFactor, String>
+{
+    use glium::LinearBlendingFactor::*;
+    match name
+    {
+        "zero" => Ok(Zero),
+        "one" => Ok(One),
+        "src_color" => Ok(SourceColor),
+        "inv_src_color" => Ok(OneMinusSourceColor),
+        "src_alpha" => Ok(SourceAlpha),
+        "inv_src_alpha" => Ok(OneMinusSourceAlpha),
+        "src_alpha_saturate" => Ok(SourceAlphaSaturate),
+        "dest_color" => Ok(DestinationColor),
+        "inv_dest_color" => Ok(OneMinusDestinationColor),
+        "dest_alpha" => Ok(DestinationAlpha),
+        "inv_dest_alpha" => Ok(OneMinusDestinationAlpha),
+        _ => Err(format!("error: unknown blend factor `{}`", name))
+    }
+}
+
+pub (super) fn depth_test_from_name(name : &str) -> Result<glium::DepthTest, String>
+{
+    use glium::DepthTest::*;
+    match name
+    {
+        "always" => Ok(Overwrite),
+        "never" => Ok(Ignore),
+        "equal" => Ok(IfEqual),
+        "notequal" => Ok(IfNotEqual),
+        "less" => Ok(IfLess),
+        "lessequal" => Ok(IfLessOrEqual),
+        "greater" => Ok(IfMore),
+        "greaterequal" => Ok(IfMoreOrEqual),
+        _ => Err(format!("error: unknown depth test `{}`", name))
+    }
+}
+
+// how a sprite or surface is sampled; everything defaults to nearest filtering and clamped edges, which suits pixel art
+#[derive(Debug, Clone, Copy)]
+pub (super) struct SamplerSettings {
+    pub (super) linear : bool,
+    pub (super) wrap : SamplerWrapFunction,
+    pub (super) mipmaps : bool,
+}
+
+impl SamplerSettings {
+    pub (super) fn new() -> SamplerSettings
+    {
+        SamplerSettings{linear : false, wrap : SamplerWrapFunction::Clamp, mipmaps : false}
+    }
+    pub (super) fn apply<'t, T>(&self, sampler : Sampler<'t, T>) -> Sampler<'t, T>
+    {
+        let (minify, magnify) = match (self.linear, self.mipmaps)
+        {
+            (false, false) => (MinifySamplerFilter::Nearest, MagnifySamplerFilter::Nearest),
+            (true, false) => (MinifySamplerFilter::Linear, MagnifySamplerFilter::Linear),
+            (false, true) => (MinifySamplerFilter::NearestMipmapNearest, MagnifySamplerFilter::Nearest),
+            (true, true) => (MinifySamplerFilter::LinearMipmapLinear, MagnifySamplerFilter::Linear),
+        };
+        sampler.minify_filter(minify).magnify_filter(magnify).wrap_function(self.wrap)
+    }
+}
+
+pub (super) fn filter_from_name(name : &str) -> Result<bool, String>
+{
+    match name
+    {
+        "nearest" => Ok(false),
+        "linear" => Ok(true),
+        _ => Err(format!("error: unknown texture filter `{}`; expected nearest or linear", name))
+    }
+}
+
+pub (super) fn wrap_from_name(name : &str) -> Result<SamplerWrapFunction, String>
+{
+    match name
+    {
+        "clamp" => Ok(SamplerWrapFunction::Clamp),
+        "repeat" => Ok(SamplerWrapFunction::Repeat),
+        "mirror" => Ok(SamplerWrapFunction::Mirror),
+        _ => Err(format!("error: unknown texture wrap mode `{}`; expected clamp, repeat, or mirror", name))
+    }
+}
+
+// reuploads a texture's pixels, so that mipmaps can be added to or removed from it
+fn rebuild_texture(display : &Backend, texture : &SrgbTexture2d, mipmaps : bool) -> Result<SrgbTexture2d, String>
+{
+    let image : glium::texture::RawImage2d<u8> = texture.read();
+    let option = if mipmaps { MipmapsOption::AutoGeneratedMipmaps } else { MipmapsOption::NoMipmap };
+    SrgbTexture2d::with_mipmaps(display, image, option).or_else(|err| Err(format!("error: failed to rebuild texture: {:?}", err)))
+}
+
+// a list of uniforms built at draw time, for when the set of uniforms isn't known at compile time
+pub (super) struct UniformList<'a> {
+    values : Vec<(&'a str, UniformValue<'a>)>,
+}
+
+impl<'a> UniformList<'a> {
+    pub (super) fn push(&mut self, name : &'a str, value : UniformValue<'a>)
+    {
+        self.values.push((name, value));
+    }
+}
+
+impl<'a> Uniforms for UniformList<'a> {
+    fn visit_values<'b, F : FnMut(&str, UniformValue<'b>)>(&'b self, mut output : F)
+    {
+        for (name, value) in &self.values
+        {
+            output(name, *value);
+        }
+    }
+}
+
+// draws a texture over a whole target (or the letterbox rectangle of the window) with the uniforms that the default program expects
+fn fullscreen_uniforms<'a>(tex : Sampler<'a, SrgbTexture2d>, matrix_command : [[f32; 4]; 4]) -> UniformList<'a>
+{
+    let identity = [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0f32],
+    ];
+    UniformList{values : vec!(
+        ("matrix_view", UniformValue::Mat4(identity)),
+        ("matrix_command", UniformValue::Mat4(matrix_command)),
+        ("tex_topleft", UniformValue::Vec2([0.0, 0.0])),
+        ("tex_bottomright", UniformValue::Vec2([1.0, 1.0])),
+        ("color_multiply", UniformValue::Vec4([1.0, 1.0, 1.0, 1.0])),
+        ("tex", UniformValue::SrgbTexture2d(tex.0, Some(tex.1))),
+    )}
+}
+
+// uniform values that scripts can give to post-process passes
+#[derive(Debug, Clone)]
+pub (super) enum ShaderValue {
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    Sprite(u64),
+    Surface(u64),
+}
+
+impl ShaderValue {
+    // sprites and surfaces that no longer exist are left unset
+    fn as_uniform_value<'a>(&self, sprites : &'a ResourceStore<SpriteSheet>, surfaces : &'a ResourceStore<Surface>) -> Option<UniformValue<'a>>
+    {
+        match self
+        {
+            ShaderValue::Float(x) => Some(UniformValue::Float(*x)),
+            ShaderValue::Vec2(x) => Some(UniformValue::Vec2(*x)),
+            ShaderValue::Vec3(x) => Some(UniformValue::Vec3(*x)),
+            ShaderValue::Vec4(x) => Some(UniformValue::Vec4(*x)),
+            ShaderValue::Sprite(id) =>
+            {
+                let sprite = sprites.get(*id)?;
+                let sampler = sprite.sampler.apply(Sampler::new(&sprite.texture));
+                Some(UniformValue::SrgbTexture2d(sampler.0, Some(sampler.1)))
+            }
+            ShaderValue::Surface(id) =>
+            {
+                let surface = surfaces.get(*id)?;
+                let sampler = surface.sampler.apply(Sampler::new(surface.texture()));
+                Some(UniformValue::SrgbTexture2d(sampler.0, Some(sampler.1)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub (super) struct PostProcessPass {
+    pub (super) id : u64,
+    pub (super) program : u64,
+    pub (super) uniforms : Vec<(String, ShaderValue)>,
+}
+
+impl PostProcessPass {
+    pub (super) fn set_uniform(&mut self, name : &str, value : ShaderValue)
+    {
+        match self.uniforms.iter_mut().find(|(other, _)| other == name)
+        {
+            Some(uniform) => uniform.1 = value,
+            None => self.uniforms.push((name.to_string(), value))
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -89,30 +476,56 @@ struct TextDrawData {
     tex_coords: glyph_brush::rusttype::Rect<f32>,
     draw_coords: glyph_brush::rusttype::Rect<i32>,
     color : [f32; 4],
+    section : usize,
+    glyph : Option<usize>, // index into the section's PendingText::glyphs, once the quads are numbered
 }
 
 impl TextDrawData
 {
+    // queued sections carry their index in the batch as their z value
     pub (crate) fn new(quad_data : glyph_brush::GlyphVertex) -> TextDrawData
     {
-        TextDrawData{tex_coords : quad_data.tex_coords, draw_coords : quad_data.pixel_coords, color : quad_data.color}
+        TextDrawData{tex_coords : quad_data.tex_coords, draw_coords : quad_data.pixel_coords, color : quad_data.color, section : quad_data.z as usize, glyph : None}
     }
 }
 
+// the state that a queued section is drawn with once the batch is flushed, which can differ from the engine's state by then
+struct PendingText {
+    matrix_view : [[f32; 4]; 4],
+    program : Rc<glium::Program>,
+    parameters : glium::DrawParameters<'static>,
+    char_effects : Vec<(bool, bool)>,
+    // the char index of every glyph that gets a quad, in the same order as the quads; glyphs are numbered by their place in here
+    glyphs : Vec<usize>,
+    effects : TextEffects,
+    decoration : TextDecoration,
+    time : f32,
+    reveal : f32,
+}
+
 pub (super) struct TextSystem {
     glyph_brush : glyph_brush::GlyphBrush<'static, TextDrawData>,
     current_font : u64,
-    font_index_counter : u64,
-    fonts : HashMap<u64, glyph_brush::FontId>,
+    // None for bitmap fonts, which the engine draws itself; handle 0 is the built-in font
+    fonts : ResourceStore<Option<glyph_brush::FontId>>,
+    // names that markup can switch to, and the fonts that bold and italic markup switch to for each font, keyed by (font, bold, italic)
+    font_names : HashMap<String, u64>,
+    font_variants : HashMap<(u64, bool, bool), u64>,
+    // fonts that glyphs missing from a font are drawn from instead, in order
+    font_fallbacks : HashMap<u64, Vec<u64>>,
     texture : glium::texture::Texture2d,
     texture_dimensions : (u32, u32),
     cached_draw : Vec<TextDrawData>,
-    display : glium::Display,
+    pending : Vec<PendingText>,
+    // every glyph in a flushed batch goes in here, and every outline and shadow copy in the other, so that each section is drawn with two calls
+    vertex_buffer : glium::VertexBuffer<Vertex>,
+    outline_buffer : glium::VertexBuffer<OutlineVertex>,
+    context : Rc<glium::backend::Context>,
 }
 
 impl TextSystem
 {
-    pub (crate) fn new(display : &glium::Display) -> TextSystem
+    pub (crate) fn new(display : &Backend) -> TextSystem
     {
         use glyph_brush::GlyphBrushBuilder;
         use glium::texture::Texture2d;
@@ -130,10 +543,15 @@ impl TextSystem
             texture,
             texture_dimensions,
             current_font : 0,
-            font_index_counter : 1,
-            fonts : HashMap::new(),
+            fonts : ResourceStore::new(),
+            font_names : HashMap::new(),
+            font_variants : HashMap::new(),
+            font_fallbacks : HashMap::new(),
             cached_draw : Vec::new(),
-            display: display.clone()
+            pending : Vec::new(),
+            vertex_buffer : glium::VertexBuffer::empty_dynamic(display, 384).unwrap(),
+            outline_buffer : glium::VertexBuffer::empty_dynamic(display, 384).unwrap(),
+            context : Rc::clone(display.get_context()),
         }
     }
     fn update_texture(texture : &mut glium::texture::Texture2d, rect : glyph_brush::rusttype::Rect<u32>, tex_data : &[u8])
@@ -168,25 +586,274 @@ impl TextSystem
         use glium::texture::MipmapsOption;
         self.texture_dimensions = new_size;
         self.glyph_brush.resize_texture(self.texture_dimensions.0, self.texture_dimensions.1);
-        self.texture = Texture2d::empty_with_format(&self.display, U8U8U8U8, MipmapsOption::NoMipmap, self.texture_dimensions.0, self.texture_dimensions.1).unwrap();
+        self.texture = Texture2d::empty_with_format(&self.context, U8U8U8U8, MipmapsOption::NoMipmap, self.texture_dimensions.0, self.texture_dimensions.1).unwrap();
+    }
+    fn font_id(&self) -> glyph_brush::FontId
+    {
+        self.handle_font_id(self.current_font)
     }
-    pub (crate) fn draw_text(&mut self, parent : &Engine, text : &String, x : f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4])
+    fn handle_font_id(&self, handle : u64) -> glyph_brush::FontId
     {
-        let font = match self.current_font
+        match self.fonts.get(handle)
         {
-            0 => glyph_brush::FontId::default(),
-            id => *self.fonts.get(&id).unwrap()
-        };
-        self.glyph_brush.queue(glyph_brush::Section {
+            Some(Some(id)) => *id,
+            _ => glyph_brush::FontId::default()
+        }
+    }
+    pub (crate) fn check_font(&self, handle : u64) -> Result<(), String>
+    {
+        if handle != 0
+        {
+            self.fonts.lookup(handle, "font")?;
+        }
+        Ok(())
+    }
+    pub (crate) fn set_font_name(&mut self, handle : u64, name : &str) -> Result<(), String>
+    {
+        self.check_font(handle)?;
+        self.font_names.insert(name.to_string(), handle);
+        Ok(())
+    }
+    pub (crate) fn set_font_variant(&mut self, handle : u64, bold : bool, italic : bool, variant : u64) -> Result<(), String>
+    {
+        self.check_font(handle)?;
+        self.check_font(variant)?;
+        self.font_variants.insert((handle, bold, italic), variant);
+        Ok(())
+    }
+    fn is_bitmap_font(&self, handle : u64) -> bool
+    {
+        self.fonts.get(handle) == Some(&None)
+    }
+    pub (crate) fn set_font_fallbacks(&mut self, handle : u64, fallbacks : Vec<u64>) -> Result<(), String>
+    {
+        self.check_font(handle)?;
+        for fallback in &fallbacks
+        {
+            self.check_font(*fallback)?;
+        }
+        if self.is_bitmap_font(handle) || fallbacks.iter().any(|fallback| self.is_bitmap_font(*fallback))
+        {
+            return Err("error: bitmap fonts can't have fallbacks or be used as fallbacks".to_string());
+        }
+        if fallbacks.is_empty()
+        {
+            self.font_fallbacks.remove(&handle);
+        }
+        else
+        {
+            self.font_fallbacks.insert(handle, fallbacks);
+        }
+        Ok(())
+    }
+    // the runs of text to draw from each font of the handle's fallback chain
+    // the handle can be a variant that base resolved to, which uses base's fallbacks if it doesn't have its own
+    fn fallback_runs<'a>(&self, handle : u64, base : u64, text : &'a str) -> Vec<(glyph_brush::FontId, &'a str)>
+    {
+        use glyph_brush::GlyphCruncher;
+        let fallbacks = self.font_fallbacks.get(&handle).or_else(|| self.font_fallbacks.get(&base));
+        let chain : Vec<glyph_brush::FontId> = std::iter::once(handle).chain(fallbacks.into_iter().flatten().cloned()).map(|handle| self.handle_font_id(handle)).collect();
+        if chain.len() == 1
+        {
+            return vec!((chain[0], text));
+        }
+        let fonts = self.glyph_brush.fonts();
+        split_font_runs(text, chain.len(), |index, ch| fonts[chain[index].0].glyph(ch).id().0 != 0).into_iter().map(|(index, run)| (chain[index], run)).collect()
+    }
+    // bold italic text falls back to whichever of bold or italic the font has, and then to the font itself
+    fn resolve_font(&self, handle : u64, bold : bool, italic : bool) -> u64
+    {
+        let mut candidates = vec!((bold, italic));
+        if bold && italic
+        {
+            candidates.push((true, false));
+            candidates.push((false, true));
+        }
+        candidates.into_iter().filter_map(|(bold, italic)| self.font_variants.get(&(handle, bold, italic)).cloned()).next().unwrap_or(handle)
+    }
+    // one section text per span, so that wrapping and alignment treat the whole string as a single paragraph
+    fn build_rich_section<'a>(&self, spans : &'a [MarkupSpan], x : f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], layout : glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>) -> Result<glyph_brush::VariedSection<'a>, String>
+    {
+        let mut text = Vec::new();
+        for span in spans
+        {
+            let handle = match &span.font
+            {
+                Some(name) => *self.font_names.get(name).ok_or_else(|| format!("error: no font is named `{}`", name))?,
+                None => self.current_font
+            };
+            let variant = self.resolve_font(handle, span.bold, span.italic);
+            if self.is_bitmap_font(variant)
+            {
+                return Err("error: bitmap fonts can't be switched to or used as variants inside markup".to_string());
+            }
+            for (font_id, run) in self.fallback_runs(variant, handle, &span.text)
+            {
+                text.push(glyph_brush::SectionText {
+                    text : run,
+                    scale : glyph_brush::rusttype::Scale::uniform(span.size.unwrap_or(size)),
+                    color : span.color_over(color),
+                    font_id,
+                });
+            }
+        }
+        Ok(glyph_brush::VariedSection {
+            screen_position : (x, y),
+            bounds : (w, h),
+            layout,
             text,
+            ..glyph_brush::VariedSection::default()
+        })
+    }
+    // drawing and measuring both go through here, so that measurements match what gets drawn
+    // the text's position is its anchor point for the given alignment, and its bounds are placed around that point the same way
+    // text is split into one section text per run of its font's fallback chain, which are laid out together like a single string
+    fn build_section<'a>(&self, text : &'a str, x : f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], layout : glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>) -> glyph_brush::VariedSection<'a>
+    {
+        let text = self.fallback_runs(self.current_font, self.current_font, text).into_iter().map(|(font_id, run)|
+        {
+            glyph_brush::SectionText {
+                text : run,
+                scale : glyph_brush::rusttype::Scale::uniform(size),
+                color,
+                font_id,
+            }
+        }).collect();
+        glyph_brush::VariedSection {
             screen_position : (x, y),
             bounds : (w, h),
-            scale : glyph_brush::rusttype::Scale::uniform(size),
-            color,
-            font_id : font,
-            ..glyph_brush::Section::default()
+            layout,
+            text,
+            ..glyph_brush::VariedSection::default()
+        }
+    }
+    // width and height of the text, from the font's advances and line metrics rather than from the pixels that the glyphs cover
+    pub (crate) fn measure_text(&mut self, text : &str, w : f32, size : f32, layout : glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>) -> (f32, f32)
+    {
+        use glyph_brush::GlyphCruncher;
+        let section = self.build_section(text, 0.0, 0.0, w, 999999999.0, size, [1.0, 1.0, 1.0, 1.0], layout);
+        match self.glyph_brush.glyph_bounds(section)
+        {
+            Some(rect) => (rect.max.x - rect.min.x, rect.max.y - rect.min.y),
+            None => (0.0, 0.0)
+        }
+    }
+    // glyph_brush can't unload fonts, so a freed font's data stays loaded; its handle goes away, along with every name, variant, and fallback that refers to it
+    pub (crate) fn free_font(&mut self, handle : u64) -> Result<(), String>
+    {
+        if handle == 0
+        {
+            return Err("error: the built-in font can't be freed".to_string());
+        }
+        self.fonts.lookup(handle, "font")?;
+        // bitmap fonts have nothing in the glyph brush
+        if let Some(Some(_)) = self.fonts.remove(handle)
+        {
+            self.rebuild_glyph_brush(None);
+        }
+        if self.current_font == handle
+        {
+            self.current_font = 0;
+        }
+        self.font_names.retain(|_, font| *font != handle);
+        self.font_variants.retain(|(font, _, _), variant| *font != handle && *variant != handle);
+        self.font_fallbacks.remove(&handle);
+        for fallbacks in self.font_fallbacks.values_mut()
+        {
+            fallbacks.retain(|fallback| *fallback != handle);
+        }
+        // like clearing them with an empty array, so that variants go back to their base font's fallbacks
+        self.font_fallbacks.retain(|_, fallbacks| !fallbacks.is_empty());
+        Ok(())
+    }
+    // glyph_brush can't remove or replace fonts, so it gets rebuilt from the fonts that are still loaded, which renumbers them
+    // the replacement, if any, takes the place of the given handle's font; queued text has to be flushed before this
+    pub (crate) fn rebuild_glyph_brush(&mut self, replacement : Option<(u64, glyph_brush::rusttype::Font<'static>)>)
+    {
+        use glyph_brush::GlyphCruncher;
+        let old_fonts = self.glyph_brush.fonts().to_vec();
+        let mut fonts = vec!(old_fonts[0].clone());
+        let (replaced, mut replacement) = match replacement
+        {
+            Some((handle, font)) => (Some(handle), Some(font)),
+            None => (None, None)
+        };
+        for (handle, font_id) in self.fonts.iter_mut()
+        {
+            if let Some(font_id) = font_id
+            {
+                let font = if replaced == Some(handle) { replacement.take().unwrap() } else { old_fonts[font_id.0].clone() };
+                *font_id = glyph_brush::FontId(fonts.len());
+                fonts.push(font);
+            }
+        }
+        self.glyph_brush = glyph_brush::GlyphBrushBuilder::using_fonts(fonts).initial_cache_size(self.texture_dimensions).build();
+        self.cached_draw.clear();
+    }
+    pub (crate) fn font_metrics(&self, size : f32) -> glyph_brush::rusttype::VMetrics
+    {
+        use glyph_brush::GlyphCruncher;
+        self.glyph_brush.fonts()[self.font_id().0].v_metrics(glyph_brush::rusttype::Scale::uniform(size))
+    }
+    pub (crate) fn draw_text(&mut self, parent : &Engine, text : &String, x : f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], layout : glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>, decoration : &TextDecoration)
+    {
+        let section = self.build_section(text, x, y, w, h, size, color, layout);
+        self.queue_section(parent, section, Vec::new(), TextEffects::new(), decoration.clone(), 0.0, std::f32::INFINITY);
+    }
+    // only the first `reveal` glyphs are drawn, and glyphs inside [wave] and [shake] move over time
+    pub (crate) fn draw_rich_text(&mut self, parent : &Engine, spans : &[MarkupSpan], x : f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], layout : glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>, effects : &TextEffects, decoration : &TextDecoration, time : f32, reveal : f32) -> Result<(), String>
+    {
+        let section = self.build_rich_section(spans, x, y, w, h, size, color, layout)?;
+        self.queue_section(parent, section, span_char_effects(spans), effects.clone(), decoration.clone(), time, reveal);
+        Ok(())
+    }
+    // the char index of each glyph that glyph_brush will make a quad for, in order, so that quads can be numbered by their place in the section
+    // glyph_brush positions a glyph for every char of the section's text except control chars, and makes a quad for those with an outline
+    // (so not spaces and zero width spaces) that aren't completely outside of the section's bounds
+    fn section_glyphs(&mut self, section : &glyph_brush::VariedSection) -> Vec<usize>
+    {
+        use glyph_brush::{GlyphCruncher, GlyphPositioner};
+        let bounds = section.layout.bounds_rect(&glyph_brush::SectionGeometry::from(section));
+        let bounds = ((bounds.min.x, bounds.min.y), (bounds.max.x, bounds.max.y));
+        let chars = section.text.iter().flat_map(|text| text.text.chars()).enumerate().filter(|(_, ch)| !ch.is_control());
+        self.glyph_brush.glyphs(section).zip(chars).filter_map(|(glyph, (index, _))|
+        {
+            let rect = glyph.pixel_bounding_box()?;
+            if quad_in_bounds((rect.min.x, rect.min.y), (rect.max.x, rect.max.y), bounds) { Some(index) } else { None }
+        }).collect()
+    }
+    // how many glyphs draw_rich_text would number in the same box, for scripts to know when a reveal is done
+    pub (crate) fn rich_glyph_count(&mut self, spans : &[MarkupSpan], w : f32, h : f32, size : f32, layout : glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>) -> Result<usize, String>
+    {
+        let section = self.build_rich_section(spans, 0.0, 0.0, w, h, size, [1.0, 1.0, 1.0, 1.0], layout)?;
+        Ok(self.section_glyphs(&section).len())
+    }
+    // text isn't drawn right away; it's batched until something else draws, the target changes, or the frame ends, and then laid out and drawn all at once
+    // that way glyph_brush sees the same sections every frame and can reuse their layouts and quads
+    // only rich text has effects, and only its glyphs get numbered; that's done after setting z, so that it shares its layout with the queued section
+    fn queue_section(&mut self, parent : &Engine, mut section : glyph_brush::VariedSection, char_effects : Vec<(bool, bool)>, effects : TextEffects, decoration : TextDecoration, time : f32, reveal : f32)
+    {
+        section.z = self.pending.len() as f32;
+        let glyphs = if char_effects.is_empty() { Vec::new() } else { self.section_glyphs(&section) };
+        self.glyph_brush.queue(section);
+        self.pending.push(PendingText {
+            matrix_view : parent.matrix_view,
+            program : Rc::clone(&parent.current_program),
+            parameters : parent.draw_parameters(),
+            char_effects,
+            glyphs,
+            effects,
+            decoration,
+            time,
+            reveal,
         });
-        
+    }
+    pub (crate) fn flush(&mut self, parent : &Engine)
+    {
+        if self.pending.is_empty()
+        {
+            return;
+        }
         let mut succeeded = false;
         while !succeeded
         {
@@ -195,13 +862,10 @@ impl TextSystem
             {
                 Ok(glyph_brush::BrushAction::Draw(quads)) =>
                 {
-                    self.draw_quads(&quads, parent);
                     self.cached_draw = quads;
+                    self.number_glyphs();
                 }
-                Ok(glyph_brush::BrushAction::ReDraw) =>
-                {
-                    self.draw_quads(&self.cached_draw, parent);
-                }
+                Ok(glyph_brush::BrushAction::ReDraw) => {}
                 Err(glyph_brush::BrushError::TextureTooSmall { suggested }) =>
                 {
                     succeeded = false;
@@ -209,6 +873,24 @@ impl TextSystem
                 }
             }
         }
+        let quads = std::mem::take(&mut self.cached_draw);
+        self.draw_quads(&quads, parent);
+        self.cached_draw = quads;
+        self.pending.clear();
+    }
+    // a section's quads come out in the same order as the glyphs that section_glyphs picked for it, so each quad's number is its place in the section
+    // redraws reuse the quads from the last time that the same sections were queued, and their glyph numbers with them
+    fn number_glyphs(&mut self)
+    {
+        let sections : Vec<usize> = self.cached_draw.iter().map(|quad| quad.section).collect();
+        for (section, range) in section_runs(&sections)
+        {
+            let count = self.pending[section].glyphs.len();
+            for (number, quad) in self.cached_draw[range].iter_mut().enumerate()
+            {
+                quad.glyph = if number < count { Some(number) } else { None };
+            }
+        }
     }
     fn process_queue(&mut self) -> Result<glyph_brush::BrushAction<TextDrawData>, glyph_brush::BrushError> 
     {
@@ -218,63 +900,192 @@ impl TextSystem
             TextDrawData::new
         ) 
     }
-    fn draw_quads(&self, quads : &Vec<TextDrawData>, parent : &Engine)
+    // each quad is one glyph, in the order that they were laid out; quads of sections without numbered glyphs are drawn without effects
+    // a section's outline and shadow copies are all drawn before any of its glyphs, so that they don't cover neighboring glyphs
+    // the whole batch is uploaded at once and each section is drawn with one call for its copies and one for its glyphs,
+    // unless its program doesn't take texture coordinates per vertex, in which case its glyphs are drawn one at a time
+    fn draw_quads(&mut self, quads : &[TextDrawData], parent : &Engine)
     {
-        let mut target = parent.get_real_draw_target();
-        for quad in quads
+        let sections : Vec<usize> = quads.iter().map(|quad| quad.section).collect();
+        let runs = section_runs(&sections);
+        let mut vertices = Vec::with_capacity(quads.len()*6);
+        let mut outline_vertices = Vec::new();
+        let mut slices = Vec::with_capacity(runs.len());
+        for (section, range) in &runs
         {
-            let tex_rect = quad.tex_coords;
-            
-            let draw_rect = quad.draw_coords;
-            let draw_size = draw_rect.max - draw_rect.min;
-            
-            let matrix_origin = [
-                [draw_size.x as f32, 0.0, 0.0, 0.0],
-                [0.0, draw_size.y as f32, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [0.0, 0.0, 0.0, 1.0],
-            ];
-            let event_matrix = [
-                [1.0, 0.0, 0.0, 0.0],
-                [0.0, 1.0, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [draw_rect.min.x as f32, draw_rect.min.y as f32, 0.0, 1.0],
-            ];
-            let matrix_command = m4mult(&event_matrix, &matrix_origin);
-            
-            let uniforms = uniform! {
-                matrix_view : parent.matrix_view.clone(),
-                matrix_command : matrix_command,
-                tex_topleft : [tex_rect.min.x, tex_rect.min.y],//quad.0.min.x, quad.0.min.y],
-                tex_bottomright : [tex_rect.max.x, tex_rect.max.y],//quad.0.max.x, quad.0.max.y],
-                color_multiply : quad.color,
-                tex : Sampler::new(&self.texture),
-            };
-            target.draw(&parent.vertex_buffer, &parent.indices, &parent.current_program, &uniforms, &glium::DrawParameters
+            let pending = &self.pending[*section];
+            let glyphs = TextSystem::glyph_transforms(pending, &quads[range.clone()]);
+            let outline_start = outline_vertices.len();
+            for ((pass_x, pass_y), radius, pass_color) in pending.decoration.passes()
             {
-                blend : glium::Blend::alpha_blending(),
-                ..Default::default()
-            }).unwrap();
-        }
-    }
+                for (quad, (offset_x, offset_y), glyph_color) in &glyphs
+                {
+                    let mut color = pass_color;
+                    color[3] *= glyph_color[3];
+                    let (min, max, tex_min, tex_max) = TextSystem::quad_rects(quad, (offset_x + pass_x, offset_y + pass_y));
+                    // glyph textures are cached at the size they're drawn at, so a pixel is a texel
+                    OutlineVertex::push_glyph(&mut outline_vertices, min, max, tex_min, tex_max, radius, radius, color);
+                }
+            }
+            let start = vertices.len();
+            for (quad, offset, color) in &glyphs
+            {
+                let (min, max, tex_min, tex_max) = TextSystem::quad_rects(quad, *offset);
+                let corners = quad_corners(min, max, tex_min, tex_max);
+                vertices.extend(corners.iter().map(|(position, texcoord)| Vertex::textured(*position, *texcoord, *color)));
+            }
+            slices.push((*section, outline_start..outline_vertices.len(), start..vertices.len(), glyphs));
+        }
+        if vertices.is_empty()
+        {
+            return;
+        }
+        write_vertices(&mut self.vertex_buffer, &self.context, &vertices);
+        if !outline_vertices.is_empty()
+        {
+            write_vertices(&mut self.outline_buffer, &self.context, &outline_vertices);
+        }
+        
+        let mut target = parent.get_real_draw_target();
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        let identity = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0f32],
+        ];
+        for (section, outline_range, range, glyphs) in slices
+        {
+            let pending = &self.pending[section];
+            if !outline_range.is_empty()
+            {
+                let uniforms = uniform! {
+                    matrix_view : pending.matrix_view,
+                    tex : Sampler::new(&self.texture),
+                };
+                target.draw(self.outline_buffer.slice(outline_range).unwrap(), &indices, &parent.outline_program, &uniforms, &pending.parameters).unwrap();
+            }
+            if range.is_empty()
+            {
+                continue;
+            }
+            if pending.program.get_attribute("vertex_texcoord").is_none()
+            {
+                for (quad, offset, color) in &glyphs
+                {
+                    self.draw_quad(&mut target, parent, pending, quad, *offset, *color);
+                }
+                continue;
+            }
+            let uniforms = uniform! {
+                matrix_view : pending.matrix_view,
+                matrix_command : identity,
+                tex_topleft : [0.0, 0.0f32],
+                tex_bottomright : [1.0, 1.0f32],
+                color_multiply : [1.0, 1.0, 1.0, 1.0f32],
+                tex : Sampler::new(&self.texture),
+            };
+            target.draw(self.vertex_buffer.slice(range).unwrap(), &indices, &pending.program, &uniforms, &pending.parameters).unwrap();
+        }
+    }
+    // the offset and color of each of a section's glyphs that has been revealed
+    fn glyph_transforms<'a>(pending : &PendingText, quads : &'a [TextDrawData]) -> Vec<(&'a TextDrawData, (f32, f32), [f32; 4])>
+    {
+        quads.iter().filter_map(|quad|
+        {
+            let (offset_x, offset_y, alpha) = match quad.glyph
+            {
+                Some(glyph) =>
+                {
+                    let char_effects = pending.char_effects.get(pending.glyphs[glyph]).cloned().unwrap_or((false, false));
+                    pending.effects.glyph_transform(glyph, char_effects, pending.time, pending.reveal)?
+                }
+                None => (0.0, 0.0, 1.0)
+            };
+            let mut color = quad.color;
+            color[3] *= alpha;
+            Some((quad, (offset_x, offset_y), color))
+        }).collect()
+    }
+    // where a quad goes in pixels and where it comes from in the texture, as top left and bottom right corners
+    fn quad_rects(quad : &TextDrawData, offset : (f32, f32)) -> ((f32, f32), (f32, f32), (f32, f32), (f32, f32))
+    {
+        let (min, max) = (quad.draw_coords.min, quad.draw_coords.max);
+        let (tex_min, tex_max) = (quad.tex_coords.min, quad.tex_coords.max);
+        ((min.x as f32 + offset.0, min.y as f32 + offset.1), (max.x as f32 + offset.0, max.y as f32 + offset.1), (tex_min.x, tex_min.y), (tex_max.x, tex_max.y))
+    }
+    fn draw_quad(&self, target : &mut glium::framebuffer::SimpleFrameBuffer, parent : &Engine, pending : &PendingText, quad : &TextDrawData, offset : (f32, f32), color : [f32; 4])
+    {
+        let tex_rect = quad.tex_coords;
+        
+        let draw_rect = quad.draw_coords;
+        let draw_size = draw_rect.max - draw_rect.min;
+        
+        let matrix_origin = [
+            [draw_size.x as f32, 0.0, 0.0, 0.0],
+            [0.0, draw_size.y as f32, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let event_matrix = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [draw_rect.min.x as f32 + offset.0, draw_rect.min.y as f32 + offset.1, 0.0, 1.0],
+        ];
+        let matrix_command = m4mult(&event_matrix, &matrix_origin);
+        
+        let uniforms = uniform! {
+            matrix_view : pending.matrix_view,
+            matrix_command : matrix_command,
+            tex_topleft : [tex_rect.min.x, tex_rect.min.y],//quad.0.min.x, quad.0.min.y],
+            tex_bottomright : [tex_rect.max.x, tex_rect.max.y],//quad.0.max.x, quad.0.max.y],
+            color_multiply : color,
+            tex : Sampler::new(&self.texture),
+        };
+        target.draw(&parent.vertex_buffer, &parent.indices, &pending.program, &uniforms, &pending.parameters).unwrap();
+    }
 }
 
 
+// draws text outlines and shadows, whatever the current program is, since it has to grow glyphs instead of just drawing them
+const OUTLINE_VERTEX_SHADER : &str = include_str!("../../data/glsl/outline_vertex.glsl");
+const OUTLINE_FRAGMENT_SHADER : &str = include_str!("../../data/glsl/outline_fragment.glsl");
+
+// used when a program's own default shaders are missing or broken
+const FALLBACK_VERTEX_SHADER : &str = include_str!("../../data/glsl/vertex.glsl");
+const FALLBACK_FRAGMENT_SHADER : &str = include_str!("../../data/glsl/fragment.glsl");
+
+// uniforms that every program has to use for sprites and primitives to be drawn in the right place
+const REQUIRED_UNIFORMS : [&str; 2] = ["matrix_view", "matrix_command"];
+
 impl Engine {
-    pub (super) fn build_glprogram(display : &glium::Display, program_path : &String, prefix : &String) -> glium::Program
+    // when the game's default shaders don't compile, the built-in ones are used instead, and the messages saying why are returned for program_errors
+    pub (super) fn build_glprogram(display : &Backend, program_path : &String, prefix : &String) -> (glium::Program, Vec<ShaderMessage>)
     {
-        let vertex_shader_src = load_string(program_path, prefix, "glsl/vertex.glsl").unwrap();
-        let fragment_shader_src = load_string(program_path, prefix, "glsl/fragment.glsl").unwrap();
-        let glprogram = glium::Program::from_source(display, &vertex_shader_src, &fragment_shader_src, None).unwrap();
-        
-        glprogram
+        match Engine::try_compile_program(display, program_path, prefix, "glsl/vertex.glsl", "glsl/fragment.glsl")
+        {
+            Ok(glprogram) => (glprogram, Vec::new()),
+            Err(mut messages) =>
+            {
+                messages.push(ShaderMessage::new("glsl/vertex.glsl + glsl/fragment.glsl", None, "using the built-in default shaders instead"));
+                eprintln!("{}", messages.iter().map(|message| message.format()).collect::<Vec<_>>().join("\n"));
+                let glprogram = glium::Program::from_source(display, FALLBACK_VERTEX_SHADER, FALLBACK_FRAGMENT_SHADER, None).expect("error: failed to compile the built-in default shaders");
+                (glprogram, messages)
+            }
+        }
+    }
+    pub (super) fn build_outline_program(display : &Backend) -> glium::Program
+    {
+        glium::Program::from_source(display, OUTLINE_VERTEX_SHADER, OUTLINE_FRAGMENT_SHADER, None).expect("error: failed to compile the built-in outline shaders")
     }
-    pub (super) fn build_vertex_buffer(display : &glium::Display) -> (glium::VertexBuffer<Vertex>, glium::index::NoIndices)
+    pub (super) fn build_vertex_buffer(display : &Backend) -> (glium::VertexBuffer<Vertex>, glium::index::NoIndices)
     {
-        let vertex1 = Vertex { position: [0.0, 0.0] };
-        let vertex2 = Vertex { position: [0.0, 1.0] };
-        let vertex3 = Vertex { position: [1.0, 0.0] };
-        let vertex4 = Vertex { position: [1.0, 1.0] };
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let vertex1 = Vertex::new(0.0, 0.0, white);
+        let vertex2 = Vertex::new(0.0, 1.0, white);
+        let vertex3 = Vertex::new(1.0, 0.0, white);
+        let vertex4 = Vertex::new(1.0, 1.0, white);
         let shape = vec![vertex1, vertex2, vertex3, vertex4];
         
         let vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
@@ -282,22 +1093,98 @@ impl Engine {
         
         (vertex_buffer, indices)
     }
+    pub (super) fn build_white_texture(display : &Backend) -> SrgbTexture2d
+    {
+        let image = glium::texture::RawImage2d::from_raw_rgba(vec!(255u8, 255, 255, 255), (1, 1));
+        SrgbTexture2d::new(display, image).unwrap()
+    }
+    
+    // the messages from the last attempt are kept in program_errors for scripts to read, and cleared when it succeeds
+    fn compile_program(&mut self, filename_vertex : &str, filename_fragment : &str) -> Result<glium::Program, String>
+    {
+        match Engine::try_compile_program(&self.display, &self.program_path, &self.prefix, filename_vertex, filename_fragment)
+        {
+            Ok(glprogram) =>
+            {
+                self.program_errors = Vec::new();
+                Ok(glprogram)
+            }
+            Err(messages) =>
+            {
+                let text = messages.iter().map(|message| message.format()).collect::<Vec<_>>().join("\n");
+                self.program_errors = messages;
+                Err(format!("error: failed to compile program:\n{}", text))
+            }
+        }
+    }
+    fn try_compile_program(display : &Backend, program_path : &String, prefix : &String, filename_vertex : &str, filename_fragment : &str) -> Result<glium::Program, Vec<ShaderMessage>>
+    {
+        let vertex_shader_src = load_string(program_path, prefix, filename_vertex).or_else(|err| Err(vec!(ShaderMessage::new(filename_vertex, None, &err))))?;
+        let fragment_shader_src = load_string(program_path, prefix, filename_fragment).or_else(|err| Err(vec!(ShaderMessage::new(filename_fragment, None, &err))))?;
+        let glprogram = match glium::Program::from_source(display, &vertex_shader_src, &fragment_shader_src, None)
+        {
+            Ok(glprogram) => glprogram,
+            Err(glium::ProgramCreationError::CompilationError(log, ..)) =>
+            {
+                // the log doesn't say which shader it's about, so check whether the vertex shader compiles next to the built-in fragment shader
+                let vertex_compiles = match glium::Program::from_source(display, &vertex_shader_src, FALLBACK_FRAGMENT_SHADER, None)
+                {
+                    Err(glium::ProgramCreationError::CompilationError(..)) => false,
+                    _ => true
+                };
+                let file = if vertex_compiles { filename_fragment } else { filename_vertex };
+                return Err(parse_shader_log(file, &log));
+            }
+            Err(glium::ProgramCreationError::LinkingError(log)) => return Err(parse_shader_log(&format!("{} + {}", filename_vertex, filename_fragment), &log)),
+            Err(err) => return Err(vec!(ShaderMessage::new(&format!("{} + {}", filename_vertex, filename_fragment), None, &format!("{}", err))))
+        };
+        let missing = REQUIRED_UNIFORMS.iter().filter(|name| glprogram.get_uniform(name).is_none()).map(|name|
+        {
+            ShaderMessage::new(filename_vertex, None, &format!("error: program does not use the standard uniform `{}`", name))
+        }).collect::<Vec<_>>();
+        if !missing.is_empty()
+        {
+            return Err(missing);
+        }
+        Ok(glprogram)
+    }
     
     pub (super) fn load_program(&mut self, filename_vertex : &str, filename_fragment : &str) -> Result<u64, String>
     {
-        let vertex_shader_src = load_string(&self.program_path, &self.prefix, filename_vertex)?;
-        let fragment_shader_src = load_string(&self.program_path, &self.prefix, filename_fragment)?;
-        let glprogram = glium::Program::from_source(&self.display, &vertex_shader_src, &fragment_shader_src, None).or_else(|x| Err(format!("failed to compile program: {}", x)))?;
+        let glprogram = self.compile_program(filename_vertex, filename_fragment)?;
+        
+        let index = self.programs.insert(Rc::new(glprogram));
         
-        let index = self.program_index_counter;
-        self.programs.insert(index, Rc::new(glprogram));
-        self.program_index_counter += 1;
+        let resource = WatchedResource::Program{id : index, vertex : filename_vertex.to_string(), fragment : filename_fragment.to_string()};
+        self.watch_file(filename_vertex, resource.clone());
+        self.watch_file(filename_fragment, resource);
         Ok(index)
     }
     
     pub (super) fn set_program(&mut self, program_id : u64) -> Result<(), String>
     {
-        self.current_program = Rc::clone(self.programs.get(&program_id).ok_or_else(|| "no such gl program".to_string())?);
+        self.current_program = Rc::clone(self.programs.lookup(program_id, "program")?);
+        Ok(())
+    }
+    
+    // text that's already queued keeps its own reference to the program, so it still gets drawn with it
+    pub (super) fn free_program(&mut self, program_id : u64) -> Result<(), String>
+    {
+        self.programs.lookup(program_id, "program")?;
+        if self.post_process.iter().any(|pass| pass.program == program_id)
+        {
+            return Err("error: cannot free a program that a post-process pass uses".to_string());
+        }
+        let program = self.programs.remove(program_id).unwrap();
+        if Rc::ptr_eq(&program, &self.current_program)
+        {
+            self.reset_program();
+        }
+        self.file_watcher.unwatch(|resource| match resource
+        {
+            WatchedResource::Program{id, ..} => *id == program_id,
+            _ => false
+        });
         Ok(())
     }
     
@@ -306,66 +1193,616 @@ impl Engine {
         self.current_program = Rc::clone(&self.default_program);
     }
     
-    pub (super) fn load_font(&mut self, fname : &str) -> u64
+    // fonts are parsed before they are handed to the glyph brush, which would panic on invalid font data
+    fn read_font(&self, fname : &str) -> Result<glyph_brush::rusttype::Font<'static>, String>
     {
         use std::io::Read as _;
-        let mut text_system = self.text_system.borrow_mut();
-        let index = text_system.font_index_counter;
         let mut bytes = Vec::new();
-        open_file(&self.program_path, &self.prefix, fname).unwrap().read_to_end(&mut bytes).unwrap();
-        let id = text_system.glyph_brush.add_font_bytes(bytes);
-        text_system.fonts.insert(index, id);
-        text_system.font_index_counter += 1;
+        open_file(&self.program_path, &self.prefix, fname)?.read_to_end(&mut bytes).or_else(|_| Err(format!("error: failed to read file `{}`", fname)))?;
+        glyph_brush::rusttype::Font::from_bytes(bytes).or_else(|err| Err(format!("error: failed to load font `{}`: {}", fname, err)))
+    }
+    
+    pub (super) fn load_font(&mut self, fname : &str) -> Result<u64, String>
+    {
+        let font = self.read_font(fname)?;
+        let index =
+        {
+            let mut text_system = self.text_system.borrow_mut();
+            let id = text_system.glyph_brush.add_font(font);
+            text_system.fonts.insert(Some(id))
+        };
+        self.watch_file(fname, WatchedResource::Font{id : index, fname : fname.to_string()});
+        Ok(index)
+    }
+    
+    // a sprite whose images are the glyphs for `chars`, in order
+    pub (super) fn load_bitmap_font_from_sprite(&mut self, sprite_id : u64, chars : &str, spacing : f32) -> Result<u64, String>
+    {
+        let spritesheet = self.get_sprite(sprite_id)?;
+        let images : Vec<((f64, f64), (f64, f64))> = spritesheet.images.iter().map(|image| (image.topleft, image.bottomright)).collect();
+        let font = BitmapFont::from_images(chars, &images, spacing)?;
+        Ok(self.add_bitmap_font(sprite_id, font, false))
+    }
+    // a BMFont .fnt file in the text format; its page image is loaded as a sprite, relative to the .fnt file
+    pub (super) fn load_bitmap_font(&mut self, fname : &str) -> Result<u64, String>
+    {
+        let font = BitmapFont::from_fnt(&load_string(&self.program_path, &self.prefix, fname)?).or_else(|err| Err(format!("{} (in `{}`)", err, fname)))?;
+        let page = std::path::Path::new(fname).with_file_name(font.page.as_ref().unwrap());
+        let sprite_id = self.load_sprite(&page.to_string_lossy(), (0.0, 0.0))?;
+        Ok(self.add_bitmap_font(sprite_id, font, true))
+    }
+    fn add_bitmap_font(&mut self, sprite_id : u64, font : BitmapFont, owns_sprite : bool) -> u64
+    {
+        let index = self.text_system.borrow_mut().fonts.insert(None);
+        self.bitmap_fonts.insert(index, (sprite_id, font, owns_sprite));
         index
     }
+    // queued text is drawn first, since it might use the font
+    pub (super) fn free_font(&mut self, font_id : u64) -> Result<(), String>
+    {
+        self.flush_text();
+        self.text_system.borrow_mut().free_font(font_id)?;
+        if let Some((sprite_id, _, true)) = self.bitmap_fonts.remove(&font_id)
+        {
+            self.free_sprite(sprite_id)?;
+        }
+        self.file_watcher.unwatch(|resource| match resource
+        {
+            WatchedResource::Font{id, ..} => *id == font_id,
+            _ => false
+        });
+        Ok(())
+    }
+    fn current_bitmap_font(&self) -> Option<u64>
+    {
+        let current = self.text_system.borrow().current_font;
+        if self.bitmap_fonts.contains_key(&current) { Some(current) } else { None }
+    }
     
-    pub (super) fn set_font(&mut self, id : u64)
+    pub (super) fn set_font(&mut self, id : u64) -> Result<(), String>
     {
         let mut text_system = self.text_system.borrow_mut();
+        text_system.check_font(id)?;
         text_system.current_font = id;
+        Ok(())
+    }
+    
+    // the format is detected from the file's contents, falling back to its extension for formats without a signature (like TGA)
+    fn decode_image(bytes : &[u8], name : &str) -> Result<image::RgbaImage, String>
+    {
+        let format = match image::guess_format(bytes)
+        {
+            Ok(format) => format,
+            Err(_) =>
+            {
+                let extension = std::path::Path::new(name).extension().and_then(|x| x.to_str()).map(|x| x.to_lowercase());
+                match extension.as_ref().map(|x| x.as_str())
+                {
+                    Some("png") => image::ImageFormat::PNG,
+                    Some("jpg") | Some("jpeg") => image::ImageFormat::JPEG,
+                    Some("gif") => image::ImageFormat::GIF,
+                    Some("webp") => image::ImageFormat::WEBP,
+                    Some("bmp") => image::ImageFormat::BMP,
+                    Some("ico") => image::ImageFormat::ICO,
+                    Some("tga") => image::ImageFormat::TGA,
+                    Some("tif") | Some("tiff") => image::ImageFormat::TIFF,
+                    Some("hdr") => image::ImageFormat::HDR,
+                    Some("pbm") | Some("pgm") | Some("ppm") | Some("pam") => image::ImageFormat::PNM,
+                    _ => return Err(format!("error: could not detect the image format of `{}`", name))
+                }
+            }
+        };
+        let image = image::load_from_memory_with_format(bytes, format).or_else(|err| Err(format!("error: failed to decode image `{}`: {}", name, err)))?;
+        Ok(image.to_rgba())
     }
     
-    pub (super) fn load_sprite(&mut self, fname : &str, origin : (f64, f64)) -> u64
+    fn texture_from_bytes(&self, bytes : &[u8], name : &str) -> Result<SrgbTexture2d, String>
     {
-        let index = self.sprite_index_counter;
-        let image = image::load(open_file(&self.program_path, &self.prefix, fname).unwrap(), image::ImageFormat::PNG).unwrap().to_rgba();
+        let image = Engine::decode_image(bytes, name)?;
         let image_dimensions = image.dimensions();
         let image = glium::texture::RawImage2d::from_raw_rgba(image.into_raw(), image_dimensions);
-        let texture = SrgbTexture2d::new(&self.display, image).unwrap();
-        
-        self.sprites.insert(index, SpriteSheet{images: vec!(SpriteImage::basic(origin, &texture)), texture});
-        
-        self.sprite_index_counter += 1;
+        SrgbTexture2d::with_mipmaps(&self.display, image, MipmapsOption::NoMipmap).or_else(|err| Err(format!("error: failed to create texture for `{}`: {:?}", name, err)))
+    }
+    
+    fn load_texture(&self, fname : &str) -> Result<SrgbTexture2d, String>
+    {
+        use std::io::Read as _;
+        let mut bytes = Vec::new();
+        open_file(&self.program_path, &self.prefix, fname)?.read_to_end(&mut bytes).or_else(|_| Err(format!("error: failed to read file `{}`", fname)))?;
+        self.texture_from_bytes(&bytes, fname)
+    }
+    
+    fn insert_sprite(&mut self, sprite : SpriteSheet) -> u64
+    {
+        self.sprites.insert(sprite)
+    }
+    // animation players and bitmap fonts that use the sprite are left alone, and give an error when they're drawn
+    pub (super) fn free_sprite(&mut self, sprite_id : u64) -> Result<(), String>
+    {
+        self.sprites.lookup(sprite_id, "sprite")?;
+        self.sprites.remove(sprite_id);
+        self.file_watcher.unwatch(|resource| match resource
+        {
+            WatchedResource::Sprite{id, ..} => *id == sprite_id,
+            _ => false
+        });
+        Ok(())
+    }
+    
+    // sprites made of a single whole image get resized to the new image when their file is reloaded; the rest keep their subimages
+    fn insert_sprite_from_file(&mut self, sprite : SpriteSheet, fname : &str, fit_image : bool) -> u64
+    {
+        let index = self.insert_sprite(sprite);
+        self.watch_file(fname, WatchedResource::Sprite{id : index, fname : fname.to_string(), fit_image});
         index
     }
     
-    pub (super) fn load_sprite_with_subimages(&mut self, fname : &str, images : Vec<SpriteImage>) -> u64
+    pub (super) fn load_sprite(&mut self, fname : &str, origin : (f64, f64)) -> Result<u64, String>
     {
-        let index = self.sprite_index_counter;
-        let image = image::load(open_file(&self.program_path, &self.prefix, fname).unwrap(), image::ImageFormat::PNG).unwrap().to_rgba();
-        let image_dimensions = image.dimensions();
-        let image = glium::texture::RawImage2d::from_raw_rgba(image.into_raw(), image_dimensions);
-        let texture = SrgbTexture2d::new(&self.display, image).unwrap();
-        
-        self.sprites.insert(index, SpriteSheet{images, texture});
+        let texture = self.load_texture(fname)?;
+        let images = vec!(SpriteImage::basic(origin, &texture));
+        Ok(self.insert_sprite_from_file(SpriteSheet::new(texture, images), fname, true))
+    }
+    
+    pub (super) fn load_sprite_with_subimages(&mut self, fname : &str, images : Vec<SpriteImage>) -> Result<u64, String>
+    {
+        let texture = self.load_texture(fname)?;
+        Ok(self.insert_sprite_from_file(SpriteSheet::new(texture, images), fname, false))
+    }
+    
+    pub (super) fn load_sprite_strip(&mut self, fname : &str, frame_dims : (f64, f64), count : usize, origin : (f64, f64)) -> Result<u64, String>
+    {
+        let texture = self.load_texture(fname)?;
+        let images = spritesheet::grid_images(texture.dimensions(), frame_dims, count, origin)?;
+        Ok(self.insert_sprite_from_file(SpriteSheet::new(texture, images), fname, false))
+    }
+    
+    // the sheet's image is looked up next to the json file
+    pub (super) fn load_sprite_sheet(&mut self, fname : &str, origin : (f64, f64)) -> Result<u64, String>
+    {
+        let text = load_string(&self.program_path, &self.prefix, fname)?;
+        let sheet = spritesheet::parse_sheet_json(&text, origin)?;
+        let image_fname = sheet.image.ok_or_else(|| format!("error: sprite sheet `{}` does not name its image", fname))?;
+        let image_path = match std::path::Path::new(fname).parent()
+        {
+            Some(parent) => parent.join(&image_fname).to_string_lossy().into_owned(),
+            None => image_fname
+        };
         
-        self.sprite_index_counter += 1;
-        index
+        let texture = self.load_texture(&image_path)?;
+        for image in &sheet.images
+        {
+            if image.bottomright.0 > texture.width() as f64 || image.bottomright.1 > texture.height() as f64
+            {
+                return Err(format!("error: sprite sheet `{}` has frames outside of its image", fname));
+            }
+        }
+        let mut spritesheet = SpriteSheet::new(texture, sheet.images);
+        // every tag doubles as an animation; images without their own duration are shown for a tenth of a second
+        for tag in &sheet.tags
+        {
+            let animation = Animation::from_tag(tag, &spritesheet.images, 0.1)?;
+            spritesheet.animations.insert(tag.name.clone(), animation);
+        }
+        spritesheet.tags = sheet.tags;
+        Ok(self.insert_sprite_from_file(spritesheet, &image_path, false))
+    }
+    
+    fn watch_file(&mut self, fname : &str, resource : WatchedResource)
+    {
+        if let Some(path) = find_file(&self.program_path, &self.prefix, fname)
+        {
+            self.file_watcher.watch(path, resource);
+        }
+    }
+    // called once per frame; resources that fail to reload keep their old version, and scripts can see why with hotreload_get_events
+    pub (crate) fn poll_hot_reload(&mut self)
+    {
+        let delta = self.framelimiter_delta;
+        for resource in self.file_watcher.poll(delta)
+        {
+            let error = self.reload_resource(&resource).err();
+            self.file_watcher.record(&resource, error);
+        }
+    }
+    fn reload_resource(&mut self, resource : &WatchedResource) -> Result<(), String>
+    {
+        match resource
+        {
+            WatchedResource::Sprite{id, fname, fit_image} =>
+            {
+                let texture = self.load_texture(fname)?;
+                let spritesheet = self.sprites.get_mut(*id).ok_or_else(|| "error: reloaded sprite no longer exists".to_string())?;
+                let texture = if spritesheet.sampler.mipmaps { rebuild_texture(&self.display, &texture, true)? } else { texture };
+                if *fit_image
+                {
+                    let origin = spritesheet.images[0].origin;
+                    spritesheet.images = vec!(SpriteImage::basic(origin, &texture));
+                }
+                spritesheet.texture = texture;
+            }
+            WatchedResource::Program{id, vertex, fragment} =>
+            {
+                let glprogram = Rc::new(self.compile_program(vertex, fragment).or_else(|err| Err(format!("error: failed to reload program `{}` + `{}`, keeping the old one: {}", vertex, fragment, err)))?);
+                if let Some(old) = self.programs.replace(*id, Rc::clone(&glprogram))
+                {
+                    if Rc::ptr_eq(&old, &self.current_program)
+                    {
+                        self.current_program = glprogram;
+                    }
+                }
+            }
+            WatchedResource::Font{id, fname} =>
+            {
+                let font = self.read_font(fname)?;
+                self.flush_text();
+                self.text_system.borrow_mut().rebuild_glyph_brush(Some((*id, font)));
+            }
+        }
+        Ok(())
+    }
+    
+    pub (super) fn get_sprite(&self, spriteindex : u64) -> Result<&SpriteSheet, String>
+    {
+        self.sprites.lookup(spriteindex, "sprite")
+    }
+    pub (super) fn get_sprite_mut(&mut self, spriteindex : u64) -> Result<&mut SpriteSheet, String>
+    {
+        self.sprites.lookup_mut(spriteindex, "sprite")
+    }
+    pub (super) fn set_sprite_mipmaps(&mut self, spriteindex : u64, mipmaps : bool) -> Result<(), String>
+    {
+        let spritesheet = self.sprites.lookup_mut(spriteindex, "sprite")?;
+        if spritesheet.sampler.mipmaps != mipmaps
+        {
+            spritesheet.texture = rebuild_texture(&self.display, &spritesheet.texture, mipmaps)?;
+            spritesheet.sampler.mipmaps = mipmaps;
+        }
+        Ok(())
+    }
+    pub (super) fn get_surface_mut(&mut self, surface_id : u64) -> Result<&mut Surface, String>
+    {
+        self.surfaces.lookup_mut(surface_id, "surface")
+    }
+    
+    pub (super) fn add_sprite_animation(&mut self, spriteindex : u64, name : &str, animation : Animation) -> Result<(), String>
+    {
+        let spritesheet = self.sprites.lookup_mut(spriteindex, "sprite")?;
+        if let Some(frame) = animation.frames.iter().find(|frame| **frame >= spritesheet.images.len())
+        {
+            return Err(format!("error: animation `{}` uses image {} but the sprite only has {} images", name, frame, spritesheet.images.len()));
+        }
+        spritesheet.animations.insert(name.to_string(), animation);
+        Ok(())
+    }
+    fn find_sprite_animation(&self, spriteindex : u64, name : &str) -> Result<Animation, String>
+    {
+        self.get_sprite(spriteindex)?.animations.get(name).cloned().ok_or_else(|| format!("error: sprite has no animation named `{}`", name))
+    }
+    
+    pub (super) fn create_animation_player(&mut self, spriteindex : u64, name : &str) -> Result<u64, String>
+    {
+        let animation = self.find_sprite_animation(spriteindex, name)?;
+        Ok(self.animations.insert(AnimationPlayer::new(spriteindex, name, animation)))
+    }
+    pub (super) fn get_animation_player(&self, index : u64) -> Result<&AnimationPlayer, String>
+    {
+        self.animations.lookup(index, "animation player")
+    }
+    pub (super) fn get_animation_player_mut(&mut self, index : u64) -> Result<&mut AnimationPlayer, String>
+    {
+        self.animations.lookup_mut(index, "animation player")
+    }
+    pub (super) fn free_animation_player(&mut self, index : u64) -> Result<(), String>
+    {
+        self.animations.lookup(index, "animation player")?;
+        self.animations.remove(index);
+        Ok(())
+    }
+    pub (super) fn play_animation(&mut self, index : u64, name : &str) -> Result<(), String>
+    {
+        let spriteindex = self.get_animation_player(index)?.sprite;
+        let animation = self.find_sprite_animation(spriteindex, name)?;
+        self.get_animation_player_mut(index)?.play(name, animation);
+        Ok(())
+    }
+    // called once per frame, after the frame is presented, with the time that frame took
+    pub (crate) fn advance_animations(&mut self)
+    {
+        let delta = self.framelimiter_delta;
+        for player in self.animations.values_mut()
+        {
+            player.advance(delta);
+        }
+    }
+    // called once per frame along with advance_animations, so that effects see the same time that animations do
+    pub (crate) fn advance_frame_time(&mut self)
+    {
+        self.frame_time += self.framelimiter_delta;
+    }
+    pub (super) fn draw_animation(&mut self, index : u64, x : f32, y : f32, xscale : f32, yscale : f32, angle : f32, color : [f32; 4]) -> Result<(), String>
+    {
+        let player = self.get_animation_player(index)?;
+        let (spriteindex, imageindex) = (player.sprite, player.image() as u64);
+        self.draw_sprite_angled(spriteindex, imageindex, x, y, xscale, yscale, angle, color)
+    }
+    
+    pub (super) fn load_sprite_from_bytes(&mut self, bytes : &[u8], origin : (f64, f64)) -> Result<u64, String>
+    {
+        let texture = self.texture_from_bytes(bytes, "<bytes>")?;
+        let images = vec!(SpriteImage::basic(origin, &texture));
+        Ok(self.insert_sprite(SpriteSheet::new(texture, images)))
     }
     
-    pub (super) fn draw_text(&mut self, text : &String, x :f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4])
+    pub (super) fn text_layout(&self) -> glyph_brush::Layout<glyph_brush::BuiltInLineBreaker>
+    {
+        glyph_brush::Layout::default_wrap().h_align(self.draw_state.halign).v_align(self.draw_state.valign)
+    }
+    // the alignment as fractions of the text's size, for bitmap font layout
+    fn text_align_factors(&self) -> (f32, f32)
+    {
+        use glyph_brush::{HorizontalAlign, VerticalAlign};
+        let halign = match self.draw_state.halign
+        {
+            HorizontalAlign::Left => 0.0,
+            HorizontalAlign::Center => 0.5,
+            HorizontalAlign::Right => 1.0,
+        };
+        let valign = match self.draw_state.valign
+        {
+            VerticalAlign::Top => 0.0,
+            VerticalAlign::Center => 0.5,
+            VerticalAlign::Bottom => 1.0,
+        };
+        (halign, valign)
+    }
+    fn bitmap_layout(&self, font_id : u64, text : &str, w : f32, h : f32, size : f32, (halign, valign) : (f32, f32)) -> (BitmapLayout, f32)
+    {
+        let font = &self.bitmap_fonts.get(&font_id).unwrap().1;
+        let scale = size/font.line_height;
+        (font.layout(text, w, h, scale, halign, valign), scale)
+    }
+    pub (super) fn measure_text(&self, text : &str, w : f32, size : f32) -> (f32, f32)
+    {
+        if let Some(font_id) = self.current_bitmap_font()
+        {
+            let layout = self.bitmap_layout(font_id, text, w, 999999999.0, size, self.text_align_factors()).0;
+            return (layout.width, layout.height);
+        }
+        let layout = self.text_layout();
+        self.text_system.borrow_mut().measure_text(text, w, size, layout)
+    }
+    // the number of glyphs that draw_text_rich numbers for its reveal, with the current font, in a box of the same size
+    pub (super) fn rich_glyph_count(&self, text : &str, w : f32, h : f32, size : f32) -> Result<usize, String>
+    {
+        let spans = parse_markup(text);
+        if let Some(font_id) = self.current_bitmap_font()
+        {
+            let text : String = spans.iter().map(|span| span.text.as_str()).collect();
+            let (layout, scale) = self.bitmap_layout(font_id, &text, w, h, size, self.text_align_factors());
+            let font = &self.bitmap_fonts.get(&font_id).unwrap().1;
+            return Ok(layout.glyphs.iter().filter(|placed| font.glyph_rect(placed, scale).is_some()).count());
+        }
+        let layout = self.text_layout();
+        self.text_system.borrow_mut().rich_glyph_count(&spans, w, h, size, layout)
+    }
+    // lines broken by the text options instead of by glyph_brush, as (text in drawing order, left, top, width) relative to the text's position, plus the total height
+    // place_lines shapes, orders, and aligns each line; lines that would go past the bottom of the box are cut off like max_lines does
+    fn layout_text_lines(&self, text : &str, w : f32, h : f32, size : f32, options : &TextOptions) -> (Vec<(String, f32, f32, f32)>, f32)
+    {
+        let (ascent, descent, line_gap) = self.font_metrics(size);
+        let line_height = ascent - descent;
+        let step = (line_height + line_gap)*options.line_spacing;
+        let mut max_lines = options.max_lines;
+        if step > 0.0
+        {
+            let fitting = ((h - line_height)/step).floor().max(0.0) as usize + 1;
+            max_lines = Some(max_lines.unwrap_or(fitting).min(fitting));
+        }
+        // lines are measured the way they're drawn, after shaping
+        let measure = |line : &str| self.measure_text(&shape_arabic(line), 999999999.0, size).0;
+        let lines = layout_lines(text, w, max_lines, options.wrap, options.ellipsis, &measure);
+        let shown_measure = |line : &str| self.measure_text(line, 999999999.0, size).0;
+        let placed = place_lines(text, &lines, options.direction, w, self.text_align_factors().0, &shown_measure);
+        
+        let height = if placed.is_empty() { 0.0 } else { (placed.len() - 1) as f32*step + line_height };
+        let top = -height*self.text_align_factors().1;
+        let placed = placed.into_iter().enumerate().map(|(number, line)| (line.text, line.left, top + number as f32*step, line.width)).collect();
+        (placed, height)
+    }
+    // width and height of text laid out like draw_text_ext does it
+    pub (super) fn measure_text_ext(&self, text : &str, w : f32, size : f32, options : &TextOptions) -> (f32, f32)
+    {
+        let (lines, height) = self.layout_text_lines(text, w, 999999999.0, size, options);
+        (lines.iter().map(|line| line.3).fold(0.0, f32::max), height)
+    }
+    // left, top, width, and height of each line that draw_text_ext would draw, relative to the text's position; empty lines are skipped
+    pub (super) fn measure_text_lines(&self, text : &str, w : f32, size : f32, options : &TextOptions) -> Vec<(f32, f32, f32, f32)>
+    {
+        let (ascent, descent, _) = self.font_metrics(size);
+        let lines = self.layout_text_lines(text, w, 999999999.0, size, options).0;
+        lines.into_iter().filter(|line| line.3 > 0.0).map(|(_, left, top, width)| (left, top, width, ascent - descent)).collect()
+    }
+    // ascent, descent, and line gap of the current font
+    pub (super) fn font_metrics(&self, size : f32) -> (f32, f32, f32)
+    {
+        if let Some(font_id) = self.current_bitmap_font()
+        {
+            let font = &self.bitmap_fonts.get(&font_id).unwrap().1;
+            let scale = size/font.line_height;
+            return (font.base*scale, (font.base - font.line_height)*scale, 0.0);
+        }
+        let metrics = self.text_system.borrow().font_metrics(size);
+        (metrics.ascent, metrics.descent, metrics.line_gap)
+    }
+    pub (super) fn draw_text(&mut self, text : &String, x :f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4]) -> Result<(), String>
+    {
+        if let Some(font_id) = self.current_bitmap_font()
+        {
+            return self.draw_bitmap_text(font_id, &[MarkupSpan::plain(text)], x, y, w, h, size, color, std::f32::INFINITY, self.text_align_factors());
+        }
+        let layout = self.text_layout();
+        self.text_system.borrow_mut().draw_text(&self, &text, x, y, w, h, size, color, layout, &self.draw_state.text_decoration);
+        Ok(())
+    }
+    // each line is drawn on its own, left and top aligned at the place that layout_text_lines picked for it
+    pub (super) fn draw_text_ext(&mut self, text : &str, x :f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], options : &TextOptions) -> Result<(), String>
+    {
+        let (lines, _) = self.layout_text_lines(text, w, h, size, options);
+        let bitmap_font = self.current_bitmap_font();
+        for (line, left, top, _) in lines
+        {
+            match bitmap_font
+            {
+                Some(font_id) => self.draw_bitmap_text(font_id, &[MarkupSpan::plain(&line)], x + left, y + top, 999999999.0, 999999999.0, size, color, std::f32::INFINITY, (0.0, 0.0))?,
+                None => self.text_system.borrow_mut().draw_text(&self, &line, x + left, y + top, 999999999.0, 999999999.0, size, color, glyph_brush::Layout::default_single_line(), &self.draw_state.text_decoration)
+            }
+        }
+        Ok(())
+    }
+    pub (super) fn draw_text_rich(&mut self, text : &str, x :f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], reveal : f32) -> Result<(), String>
     {
-        self.text_system.borrow_mut().draw_text(&self, &text, x, y, w, h, size, color);
+        let spans = parse_markup(text);
+        if let Some(font_id) = self.current_bitmap_font()
+        {
+            return self.draw_bitmap_text(font_id, &spans, x, y, w, h, size, color, reveal, self.text_align_factors());
+        }
+        let layout = self.text_layout();
+        let time = self.frame_time as f32;
+        self.text_system.borrow_mut().draw_rich_text(&self, &spans, x, y, w, h, size, color, layout, &self.draw_state.text_effects, &self.draw_state.text_decoration, time, reveal)
+    }
+    // bitmap fonts have a single size and style, so markup only changes their color and effects
+    fn draw_bitmap_text(&mut self, font_id : u64, spans : &[MarkupSpan], x : f32, y : f32, w : f32, h : f32, size : f32, color : [f32; 4], reveal : f32, align : (f32, f32)) -> Result<(), String>
+    {
+        let (sprite_id, font, _) = self.bitmap_fonts.get(&font_id).unwrap();
+        let sprite_id = *sprite_id;
+        let spritesheet = self.sprites.get(sprite_id).ok_or_else(|| "error: the sprite that this bitmap font draws from no longer exists".to_string())?;
+        let text : String = spans.iter().map(|span| span.text.as_str()).collect();
+        let char_spans : Vec<usize> = spans.iter().enumerate().flat_map(|(index, span)| std::iter::repeat(index).take(span.text.chars().count())).collect();
+        let char_effects = span_char_effects(spans);
+        let (layout, scale) = self.bitmap_layout(font_id, &text, w, h, size, align);
+        let time = self.frame_time as f32;
+        let mut glyphs = Vec::new();
+        // like with other fonts, only glyphs that have something to draw are numbered
+        let mut number = 0;
+        for placed in &layout.glyphs
+        {
+            let (position, topleft, bottomright) = match font.glyph_rect(placed, scale)
+            {
+                Some(rect) => rect,
+                None => continue
+            };
+            let glyph = number;
+            number += 1;
+            let span_index = char_spans[placed.index];
+            let effects = char_effects[placed.index];
+            let (offset_x, offset_y, alpha) = match self.draw_state.text_effects.glyph_transform(glyph, effects, time, reveal)
+            {
+                Some(transform) => transform,
+                None => continue
+            };
+            let mut glyph_color = spans[span_index].color_over(color);
+            glyph_color[3] *= alpha;
+            let image = SpriteImage::extended((0.0, 0.0), topleft, bottomright);
+            glyphs.push((image, (x + position.0 + offset_x, y + position.1 + offset_y), glyph_color));
+        }
+        let (tex_w, tex_h) = (spritesheet.texture.width() as f32, spritesheet.texture.height() as f32);
+        let quads : Vec<((f32, f32), (f32, f32), (f32, f32), (f32, f32))> = glyphs.iter().map(|(image, (glyph_x, glyph_y), _)|
+        {
+            let (width, height) = ((image.bottomright.0 - image.topleft.0) as f32*scale, (image.bottomright.1 - image.topleft.1) as f32*scale);
+            let tex_min = (image.topleft.0 as f32 / tex_w, image.topleft.1 as f32 / tex_h);
+            let tex_max = (image.bottomright.0 as f32 / tex_w, image.bottomright.1 as f32 / tex_h);
+            ((*glyph_x, *glyph_y), (glyph_x + width, glyph_y + height), tex_min, tex_max)
+        }).collect();
+        // like with other fonts, every outline and shadow copy goes under every glyph
+        let mut outline_vertices = Vec::new();
+        for ((pass_x, pass_y), radius, pass_color) in self.draw_state.text_decoration.passes()
+        {
+            for ((min, max, tex_min, tex_max), (_, _, glyph_color)) in quads.iter().zip(&glyphs)
+            {
+                let mut color = pass_color;
+                color[3] *= glyph_color[3];
+                // the font's texels are stretched to its scale
+                OutlineVertex::push_glyph(&mut outline_vertices, (min.0 + pass_x, min.1 + pass_y), (max.0 + pass_x, max.1 + pass_y), *tex_min, *tex_max, radius, radius/scale, color);
+            }
+        }
+        self.draw_outlines(sprite_id, &outline_vertices)?;
+        if self.current_program.get_attribute("vertex_texcoord").is_none()
+        {
+            let spritesheet = self.get_sprite(sprite_id)?;
+            for (image, (glyph_x, glyph_y), glyph_color) in &glyphs
+            {
+                let matrix = Engine::sprite_matrix(*glyph_x, *glyph_y, scale, scale, 0.0);
+                self.draw_texture_region(&spritesheet.texture, spritesheet.sampler, image, matrix, *glyph_color, false);
+            }
+            return Ok(());
+        }
+        let mut vertices = Vec::with_capacity(glyphs.len()*6);
+        for ((min, max, tex_min, tex_max), (_, _, glyph_color)) in quads.iter().zip(&glyphs)
+        {
+            let corners = quad_corners(*min, *max, *tex_min, *tex_max);
+            vertices.extend(corners.iter().map(|(position, texcoord)| Vertex::textured(*position, *texcoord, *glyph_color)));
+        }
+        self.draw_sprite_triangles(sprite_id, &vertices)
+    }
+    // draws outline and shadow copies of a sprite's glyphs with the built-in outline program, with one call
+    fn draw_outlines(&mut self, sprite_id : u64, vertices : &[OutlineVertex]) -> Result<(), String>
+    {
+        if vertices.is_empty()
+        {
+            return Ok(());
+        }
+        self.flush_text();
+        write_vertices(&mut self.outline_buffer, &self.display, vertices);
+        let spritesheet = self.get_sprite(sprite_id)?;
+        let uniforms = uniform! {
+            matrix_view : self.matrix_view,
+            tex : spritesheet.sampler.apply(Sampler::new(&spritesheet.texture)),
+        };
+        let vertex_buffer = self.outline_buffer.slice(0..vertices.len()).unwrap();
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        self.get_real_draw_target().draw(vertex_buffer, &indices, &self.outline_program, &uniforms, &self.draw_parameters()).unwrap();
+        Ok(())
+    }
+    // draws a batch of triangles that already have their positions and texture coordinates worked out, with one call
+    fn draw_sprite_triangles(&mut self, sprite_id : u64, vertices : &[Vertex]) -> Result<(), String>
+    {
+        if vertices.is_empty()
+        {
+            return Ok(());
+        }
+        self.flush_text();
+        write_vertices(&mut self.primitive_buffer, &self.display, vertices);
+        let spritesheet = self.get_sprite(sprite_id)?;
+        let matrix_command = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0f32],
+        ];
+        
+        let uniforms = uniform! {
+            matrix_view : self.matrix_view,
+            matrix_command : matrix_command,
+            tex_topleft : [0.0, 0.0f32],
+            tex_bottomright : [1.0, 1.0f32],
+            color_multiply : [1.0, 1.0, 1.0, 1.0f32],
+            tex : spritesheet.sampler.apply(Sampler::new(&spritesheet.texture)),
+        };
+        let vertex_buffer = self.primitive_buffer.slice(0..vertices.len()).unwrap();
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        self.get_real_draw_target().draw(vertex_buffer, &indices, &self.current_program, &uniforms, &self.draw_parameters()).unwrap();
+        Ok(())
     }
-    pub (super) fn draw_sprite(&mut self, spriteindex : u64, imageindex : u64, x : f32, y : f32)
+    pub (super) fn draw_sprite(&mut self, spriteindex : u64, imageindex : u64, x : f32, y : f32) -> Result<(), String>
     {
         self.draw_sprite_scaled(spriteindex, imageindex, x, y, 1.0, 1.0)
     }
-    pub (super) fn draw_sprite_scaled(&mut self, spriteindex : u64, imageindex : u64, x : f32, y : f32, xscale : f32, yscale : f32)
+    pub (super) fn draw_sprite_scaled(&mut self, spriteindex : u64, imageindex : u64, x : f32, y : f32, xscale : f32, yscale : f32) -> Result<(), String>
     {
-        self.draw_sprite_angled(spriteindex, imageindex, x, y, xscale, yscale, 0.0)
+        self.draw_sprite_angled(spriteindex, imageindex, x, y, xscale, yscale, 0.0, [1.0, 1.0, 1.0, 1.0])
     }
-    pub (super) fn draw_sprite_angled(&mut self, spriteindex : u64, imageindex : u64, x : f32, y : f32, xscale : f32, yscale : f32, angle : f32)
+    fn sprite_matrix(x : f32, y : f32, xscale : f32, yscale : f32, angle : f32) -> [[f32; 4]; 4]
     {
         let angle_radians = deg2rad(angle as f64);
         let angle_cos = angle_radians.cos() as f32;
@@ -384,19 +1821,57 @@ impl Engine {
             [0.0, 0.0, 0.0, 1.0],
         ];
         
-        let matrix = m4mult(&matrix_pos, &matrix_rotscale);
-        
-        self.draw_sprite_transformed(spriteindex, imageindex, matrix)
+        m4mult(&matrix_pos, &matrix_rotscale)
+    }
+    pub (super) fn draw_sprite_angled(&mut self, spriteindex : u64, imageindex : u64, x : f32, y : f32, xscale : f32, yscale : f32, angle : f32, color : [f32; 4]) -> Result<(), String>
+    {
+        let matrix = Engine::sprite_matrix(x, y, xscale, yscale, angle);
+        self.draw_sprite_transformed(spriteindex, imageindex, matrix, color)
+    }
+    pub (super) fn draw_sprite_transformed(&mut self, spriteindex : u64, imageindex : u64, matrix : [[f32; 4]; 4], color : [f32; 4]) -> Result<(), String>
+    {
+        let spritesheet = self.get_sprite(spriteindex)?;
+        let image = spritesheet.images.get(imageindex as usize % spritesheet.images.len()).unwrap();
+        self.draw_texture_region(&spritesheet.texture, spritesheet.sampler, image, matrix, color, false);
+        Ok(())
+    }
+    // fills a rectangle with the sprite's texture repeated from the given offset, with a single quad
+    // the sprite has to use repeat or mirror wrapping, and only works as expected if its image covers its whole texture
+    pub (super) fn draw_sprite_tiled(&mut self, spriteindex : u64, x : f32, y : f32, width : f64, height : f64, offset : (f64, f64), color : [f32; 4]) -> Result<(), String>
+    {
+        let spritesheet = self.get_sprite(spriteindex)?;
+        if let SamplerWrapFunction::Clamp = spritesheet.sampler.wrap
+        {
+            return Err("error: sprites must use repeat or mirror wrapping to be drawn tiled".to_string());
+        }
+        let region = SpriteImage::extended((0.0, 0.0), offset, (offset.0 + width, offset.1 + height));
+        let matrix = Engine::sprite_matrix(x, y, 1.0, 1.0, 0.0);
+        self.draw_texture_region(&spritesheet.texture, spritesheet.sampler, &region, matrix, color, false);
+        Ok(())
     }
-    pub (super) fn draw_sprite_transformed(&mut self, spriteindex : u64, imageindex : u64, matrix : [[f32; 4]; 4])
+    // draws a rectangle of the given subimage, measured from its top left corner, with the top left of the rectangle at the drawing position
+    pub (super) fn draw_sprite_part(&mut self, spriteindex : u64, imageindex : u64, left : f64, top : f64, width : f64, height : f64, x : f32, y : f32, xscale : f32, yscale : f32, color : [f32; 4]) -> Result<(), String>
     {
-        let spritesheet = self.sprites.get(&spriteindex).unwrap();
-        let texture = &spritesheet.texture;
+        let spritesheet = self.get_sprite(spriteindex)?;
+        let image = spritesheet.images.get(imageindex as usize % spritesheet.images.len()).unwrap();
+        
+        let topleft = ((image.topleft.0 + left.max(0.0)).min(image.bottomright.0), (image.topleft.1 + top.max(0.0)).min(image.bottomright.1));
+        let bottomright = ((image.topleft.0 + left + width).min(image.bottomright.0).max(topleft.0), (image.topleft.1 + top + height).min(image.bottomright.1).max(topleft.1));
+        // parts that got clipped on the top or left still have to start where the unclipped part would have
+        let origin = (image.topleft.0 + left - topleft.0, image.topleft.1 + top - topleft.1);
+        let part = SpriteImage::extended(origin, topleft, bottomright);
         
+        let matrix = Engine::sprite_matrix(x, y, xscale, yscale, 0.0);
+        self.draw_texture_region(&spritesheet.texture, spritesheet.sampler, &part, matrix, color, false);
+        Ok(())
+    }
+    // surfaces are stored bottom-up like every other framebuffer, so they have to be drawn with flip_y
+    fn draw_texture_region(&self, texture : &SrgbTexture2d, sampler : SamplerSettings, image : &SpriteImage, matrix : [[f32; 4]; 4], color : [f32; 4], flip_y : bool)
+    {
+        self.flush_text();
         let tex_w = texture.width() as f32;
         let tex_h = texture.height() as f32;
         
-        let image = spritesheet.images.get(imageindex as usize % spritesheet.images.len()).unwrap();
         let x_dim = (image.bottomright.0 - image.topleft.0) as f32;
         let y_dim = (image.bottomright.1 - image.topleft.1) as f32;
         let xorigin = (image.origin.0) as f32;
@@ -410,26 +1885,244 @@ impl Engine {
         ];
         let matrix_command = m4mult(&matrix, &matrix_origin);
         
+        let (tex_top, tex_bottom) = if flip_y { (tex_h as f64 - image.topleft.1, tex_h as f64 - image.bottomright.1) } else { (image.topleft.1, image.bottomright.1) };
+        
         let uniforms = uniform! {
             matrix_view : self.matrix_view,
             matrix_command : matrix_command,
-            tex_topleft : [image.topleft.0 as f32 / tex_w, image.topleft.1 as f32 / tex_h],
-            tex_bottomright : [image.bottomright.0 as f32 / tex_w, image.bottomright.1 as f32 / tex_h],
-            color_multiply : [1.0, 1.0, 1.0, 1.0f32],
-            tex : Sampler::new(texture).minify_filter(MinifySamplerFilter::Nearest).magnify_filter(MagnifySamplerFilter::Nearest),
+            tex_topleft : [image.topleft.0 as f32 / tex_w, tex_top as f32 / tex_h],
+            tex_bottomright : [image.bottomright.0 as f32 / tex_w, tex_bottom as f32 / tex_h],
+            color_multiply : color,
+            tex : sampler.apply(Sampler::new(texture)),
         };
-        self.get_real_draw_target().draw(&self.vertex_buffer, &self.indices, &self.current_program, &uniforms, &glium::DrawParameters
+        self.get_real_draw_target().draw(&self.vertex_buffer, &self.indices, &self.current_program, &uniforms, &self.draw_parameters()).unwrap();
+    }
+    
+    // primitives are given in world coordinates and sample a single white texel; ones in a single color are drawn in white and tinted by color_multiply,
+    // so that they show up in that color whatever the program does with vertex colors, but gradients need a program that takes vertex colors
+    pub (super) fn draw_primitive(&mut self, vertices : &[Vertex], primitive : glium::index::PrimitiveType) -> Result<(), String>
+    {
+        if vertices.is_empty()
         {
-            blend : glium::Blend::alpha_blending(),
+            return Ok(());
+        }
+        let first_color = vertices[0].vertex_color;
+        let (vertices, color_multiply) : (Vec<Vertex>, [f32; 4]) = if vertices.iter().all(|vertex| vertex.vertex_color == first_color)
+        {
+            (vertices.iter().map(|vertex| Vertex{vertex_color : [1.0, 1.0, 1.0, 1.0], ..*vertex}).collect(), first_color)
+        }
+        else
+        {
+            if self.current_program.get_attribute("vertex_color").is_none()
+            {
+                return Err("error: gradients can't be drawn with the current program, because it has no `vertex_color` attribute".to_string());
+            }
+            (vertices.to_vec(), [1.0, 1.0, 1.0, 1.0])
+        };
+        self.flush_text();
+        write_vertices(&mut self.primitive_buffer, &self.display, &vertices);
+        let vertex_buffer = self.primitive_buffer.slice(0..vertices.len()).unwrap();
+        let matrix_command = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0f32],
+        ];
+        
+        let uniforms = uniform! {
+            matrix_view : self.matrix_view,
+            matrix_command : matrix_command,
+            tex_topleft : [0.0, 0.0f32],
+            tex_bottomright : [0.0, 0.0f32],
+            color_multiply : color_multiply,
+            tex : Sampler::new(&self.white_texture).minify_filter(MinifySamplerFilter::Nearest).magnify_filter(MagnifySamplerFilter::Nearest),
+        };
+        self.get_real_draw_target().draw(vertex_buffer, &glium::index::NoIndices(primitive), &self.current_program, &uniforms, &self.draw_parameters()).unwrap();
+        Ok(())
+    }
+    fn push_line_quad(vertices : &mut Vec<Vertex>, from : (f32, f32), to : (f32, f32), width : f32, color_from : [f32; 4], color_to : [f32; 4])
+    {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let len = (dx*dx + dy*dy).sqrt();
+        if len == 0.0
+        {
+            return;
+        }
+        let nx = -dy/len * width/2.0;
+        let ny = dx/len * width/2.0;
+        let a = Vertex::new(from.0 + nx, from.1 + ny, color_from);
+        let b = Vertex::new(from.0 - nx, from.1 - ny, color_from);
+        let c = Vertex::new(to.0 + nx, to.1 + ny, color_to);
+        let d = Vertex::new(to.0 - nx, to.1 - ny, color_to);
+        vertices.extend_from_slice(&[a, b, c, c, b, d]);
+    }
+    pub (super) fn draw_line(&mut self, from : (f32, f32), to : (f32, f32), width : f32, color_from : [f32; 4], color_to : [f32; 4]) -> Result<(), String>
+    {
+        let mut vertices = Vec::with_capacity(6);
+        Engine::push_line_quad(&mut vertices, from, to, width, color_from, color_to);
+        self.draw_primitive(&vertices, glium::index::PrimitiveType::TrianglesList)
+    }
+    // points and colors must be the same length; filled polygons are drawn as a triangle fan, so they must be convex
+    // an outline width of 0 draws the polygon filled
+    pub (super) fn draw_polygon(&mut self, points : &[(f32, f32)], colors : &[[f32; 4]], outline : f32) -> Result<(), String>
+    {
+        if points.len() < 2 || points.len() != colors.len()
+        {
+            return Ok(());
+        }
+        if outline > 0.0
+        {
+            let mut vertices = Vec::with_capacity(points.len()*6);
+            for i in 0..points.len()
+            {
+                let j = (i+1) % points.len();
+                Engine::push_line_quad(&mut vertices, points[i], points[j], outline, colors[i], colors[j]);
+            }
+            self.draw_primitive(&vertices, glium::index::PrimitiveType::TrianglesList)
+        }
+        else
+        {
+            let vertices = points.iter().zip(colors.iter()).map(|(point, color)| Vertex::new(point.0, point.1, *color)).collect::<Vec<_>>();
+            self.draw_primitive(&vertices, glium::index::PrimitiveType::TriangleFan)
+        }
+    }
+    pub (super) fn draw_rectangle(&mut self, x1 : f32, y1 : f32, x2 : f32, y2 : f32, colors : [[f32; 4]; 4], outline : f32) -> Result<(), String>
+    {
+        self.draw_polygon(&[(x1, y1), (x2, y1), (x2, y2), (x1, y2)], &colors, outline)
+    }
+    pub (super) fn draw_circle(&mut self, x : f32, y : f32, radius : f32, segments : u32, color_center : [f32; 4], color_edge : [f32; 4], outline : f32) -> Result<(), String>
+    {
+        let segments = segments.max(3);
+        let points = (0..segments).map(|i|
+        {
+            let angle = i as f64 / segments as f64 * std::f64::consts::PI * 2.0;
+            (x + radius * angle.cos() as f32, y + radius * angle.sin() as f32)
+        }).collect::<Vec<_>>();
+        if outline > 0.0
+        {
+            self.draw_polygon(&points, &vec!(color_edge; points.len()), outline)
+        }
+        else
+        {
+            let mut vertices = Vec::with_capacity(points.len()+2);
+            vertices.push(Vertex::new(x, y, color_center));
+            vertices.extend(points.iter().map(|point| Vertex::new(point.0, point.1, color_edge)));
+            vertices.push(Vertex::new(points[0].0, points[0].1, color_edge));
+            self.draw_primitive(&vertices, glium::index::PrimitiveType::TriangleFan)
+        }
+    }
+    
+    pub (crate) fn draw_parameters(&self) -> glium::DrawParameters<'static>
+    {
+        let dims = self.target_dims();
+        let viewport = self.current_camera.and_then(|id| self.cameras.get(id)).and_then(|camera| camera.glium_viewport(dims));
+        let scissor = self.draw_state.scissor.map(|(x, y, w, h)|
+        {
+            let bottom = dims.1 as f64 - (y + h);
+            glium::Rect{left : x.max(0.0) as u32, bottom : bottom.max(0.0) as u32, width : w.max(0.0) as u32, height : h.max(0.0) as u32}
+        });
+        glium::DrawParameters
+        {
+            blend : self.draw_state.blend,
+            depth : glium::Depth
+            {
+                test : self.draw_state.depth_test,
+                write : self.draw_state.depth_write,
+                ..Default::default()
+            },
+            color_mask : self.draw_state.color_mask,
+            viewport,
+            scissor,
             ..Default::default()
-        }).unwrap();
+        }
+    }
+    
+    pub (crate) fn target_dims(&self) -> (u32, u32)
+    {
+        match self.surface_target.last()
+        {
+            Some(index) => self.surfaces.get(*index).unwrap().dims,
+            None => (self.draw_w, self.draw_h)
+        }
+    }
+    
+    // the view matrix maps world coordinates onto the current draw target, through the current camera if there is one
+    pub (crate) fn update_view_matrix(&mut self)
+    {
+        let dims = self.target_dims();
+        let camera_matrix = self.current_camera.and_then(|id| self.cameras.get(id)).map(|camera| camera.view_matrix(dims));
+        self.matrix_view = camera_matrix.unwrap_or([
+            [2.0/dims.0 as f32, 0.0, 0.0, 0.0],
+            [0.0, -2.0/dims.1 as f32, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0, 1.0f32],
+        ]);
+        // everything is drawn at z = 0, so the view matrix is also what places draws at the current depth
+        self.matrix_view[3][2] = self.draw_state.depth_value;
+    }
+    
+    pub (super) fn create_camera(&mut self) -> u64
+    {
+        self.cameras.insert(Camera::new())
+    }
+    
+    pub (super) fn get_camera_mut(&mut self, camera_id : u64) -> Result<&mut Camera, String>
+    {
+        self.cameras.lookup_mut(camera_id, "camera")
+    }
+    
+    pub (super) fn get_camera(&self, camera_id : u64) -> Result<&Camera, String>
+    {
+        self.cameras.lookup(camera_id, "camera")
+    }
+    
+    // drawing goes back to not using a camera if the one being freed is applied
+    pub (super) fn free_camera(&mut self, camera_id : u64) -> Result<(), String>
+    {
+        self.cameras.lookup(camera_id, "camera")?;
+        self.cameras.remove(camera_id);
+        if self.current_camera == Some(camera_id)
+        {
+            self.apply_camera(None)?;
+        }
+        Ok(())
+    }
+    
+    pub (super) fn apply_camera(&mut self, camera_id : Option<u64>) -> Result<(), String>
+    {
+        if let Some(id) = camera_id
+        {
+            self.get_camera(id)?;
+        }
+        self.current_camera = camera_id;
+        self.update_view_matrix();
+        Ok(())
+    }
+    
+    // clears the color and depth of whatever is currently being drawn to, ignoring the scissor rectangle
+    pub (super) fn clear_draw_target(&self, color : [f32; 4]) -> Result<(), String>
+    {
+        // surfaces can be cleared whenever they're the target, but the screen only exists while drawing
+        if self.surface_target.is_empty() && self.default_surface.is_none()
+        {
+            return Err("error: cannot clear the screen before drawing has started".to_string());
+        }
+        self.flush_text();
+        self.get_real_draw_target().clear_color_srgb_and_depth((color[0], color[1], color[2], color[3]), 1.0);
+        Ok(())
+    }
+    
+    // anything that draws to, reads from, or switches away from the current target has to draw batched text first to keep the draw order
+    pub (crate) fn flush_text(&self)
+    {
+        self.text_system.borrow_mut().flush(self);
     }
     
     pub (crate) fn get_real_draw_target<'a>(&'a self) -> glium::framebuffer::SimpleFrameBuffer<'a>
     {
         let target = match self.surface_target.last()
         {
-            Some(index) => self.surfaces.get(&index).unwrap(),
+            Some(index) => self.surfaces.get(*index).unwrap(),
             None => self.default_surface.as_ref().unwrap()
         };
         target.as_framebuffer(&self.display)
@@ -437,62 +2130,306 @@ impl Engine {
     
     pub (crate) fn render_begin(&mut self)
     {
-        let target = self.display.draw();
-        let dims = target.get_dimensions();
+        // text queued by the step script belongs to the previous frame, not the one that's about to be cleared
+        if self.default_surface.is_some() || !self.surface_target.is_empty()
+        {
+            self.flush_text();
+        }
+        let (target, dims) = match &self.display
+        {
+            Backend::Windowed(display) =>
+            {
+                let target = display.draw();
+                let dims = target.get_dimensions();
+                (Some(target), dims)
+            }
+            Backend::Headless(_, dims) => (None, *dims)
+        };
         
-        if let Some(default_surface) = &self.default_surface
+        self.window_dims = dims;
+        let dims = match self.virtual_resolution
         {
-            if default_surface.dims == dims
+            Some((virtual_dims, mode)) =>
+            {
+                let rect = letterbox_rect(virtual_dims, self.window_dims, mode);
+                self.input_handler.mouse_transform = Some((rect, virtual_dims));
+                virtual_dims
+            }
+            None =>
             {
-                default_surface.clear_color_infinite_depth(&self.display, (0.5, 0.5, 0.5, 1.0));
-                self.draw_target = Some(target);
-                return;
+                self.input_handler.mouse_transform = None;
+                dims
             }
+        };
+        
+        if self.default_surface.as_ref().map(|surface| surface.dims) != Some(dims)
+        {
+            self.draw_w = dims.0;
+            self.draw_h = dims.1;
+            self.default_surface = Some(Surface::new(&self.display, dims));
         }
-        self.draw_w = dims.0;
-        self.draw_h = dims.1;
-        self.matrix_view = [
-            [2.0/dims.0 as f32, 0.0, 0.0, 0.0],
-            [0.0, -2.0/dims.1 as f32, 0.0, 0.0],
-            [0.0, 0.0, 1.0, 0.0],
-            [-1.0, 1.0, 0.0, 1.0f32],
-        ];
-        self.default_surface =  Some(Surface::new(&self.display, target.get_dimensions()));
-        self.default_surface.as_ref().unwrap().clear_color_infinite_depth(&self.display, (0.5, 0.5, 0.5, 1.0));
-        self.draw_target = Some(target);
+        // without automatic clearing, the previous frame stays on the default surface; its depth is still reset
+        let default_surface = self.default_surface.as_ref().unwrap();
+        if self.auto_clear
+        {
+            let color = self.clear_color;
+            default_surface.clear_color_infinite_depth(&self.display, (color[0], color[1], color[2], color[3]));
+        }
+        else
+        {
+            default_surface.as_framebuffer(&self.display).clear_depth(1.0);
+        }
+        self.draw_target = target;
+        self.update_view_matrix();
+    }
+    
+    pub (super) fn set_virtual_resolution(&mut self, virtual_resolution : Option<((u32, u32), ScaleMode)>) -> Result<(), String>
+    {
+        if let Some(((w, h), _)) = virtual_resolution
+        {
+            if w == 0 || h == 0
+            {
+                return Err("error: virtual resolution must be at least 1x1".to_string());
+            }
+        }
+        self.virtual_resolution = virtual_resolution;
+        Ok(())
+    }
+    
+    pub (super) fn create_surface(&mut self, dims : (u32, u32)) -> u64
+    {
+        self.surfaces.insert(Surface::new(&self.display, dims))
+    }
+    
+    pub (super) fn free_surface(&mut self, surface_id : u64) -> Result<(), String>
+    {
+        self.surfaces.lookup(surface_id, "surface")?;
+        if self.surface_target.contains(&surface_id)
+        {
+            return Err("error: cannot free a surface while it is being drawn to".to_string());
+        }
+        self.surfaces.remove(surface_id);
+        Ok(())
+    }
+    
+    pub (super) fn set_surface_target(&mut self, surface_id : u64) -> Result<(), String>
+    {
+        self.surfaces.lookup(surface_id, "surface")?;
+        self.flush_text();
+        self.surface_target.push(surface_id);
+        // whatever gets drawn next makes the mipmaps stale
+        self.surfaces.get(surface_id).unwrap().mipmaps_stale.set(true);
+        self.update_view_matrix();
+        Ok(())
+    }
+    
+    pub (super) fn reset_surface_target(&mut self)
+    {
+        self.flush_text();
+        self.surface_target.pop();
+        self.update_view_matrix();
+    }
+    
+    pub (super) fn draw_surface(&mut self, surface_id : u64, x : f32, y : f32, xscale : f32, yscale : f32, color : [f32; 4]) -> Result<(), String>
+    {
+        if self.surface_target.contains(&surface_id)
+        {
+            return Err("error: cannot draw a surface while it is being drawn to".to_string());
+        }
+        self.flush_text();
+        let surface = self.surfaces.lookup(surface_id, "surface")?;
+        let texture = surface.texture();
+        let image = SpriteImage::basic((0.0, 0.0), texture);
+        let matrix = Engine::sprite_matrix(x, y, xscale, yscale, 0.0);
+        self.draw_texture_region(texture, surface.sampler, &image, matrix, color, true);
+        Ok(())
+    }
+    
+    // None reads back the default surface, which holds the frame currently being drawn (or the last one, before drawing starts)
+    pub (super) fn read_surface(&self, surface_id : Option<u64>) -> Result<(Vec<u8>, (u32, u32)), String>
+    {
+        self.flush_text();
+        match surface_id
+        {
+            Some(id) =>
+            {
+                let surface = self.surfaces.lookup(id, "surface")?;
+                Ok((surface.read_rgba(), surface.dims))
+            }
+            None => self.read_default_surface()
+        }
+    }
+    
+    pub (super) fn save_surface(&self, surface_id : Option<u64>, fname : &str) -> Result<(), String>
+    {
+        let (pixels, (w, h)) = self.read_surface(surface_id)?;
+        let path = output_path(&self.program_path, &self.prefix, fname);
+        image::save_buffer(&path, &pixels, w, h, image::ColorType::RGBA(8)).or_else(|err| Err(format!("error: failed to save image to `{}`: {}", path, err)))
+    }
+    
+    pub (crate) fn read_default_surface(&self) -> Result<(Vec<u8>, (u32, u32)), String>
+    {
+        self.flush_text();
+        let surface = self.default_surface.as_ref().ok_or_else(|| "error: nothing has been rendered yet".to_string())?;
+        Ok((surface.read_rgba(), surface.dims))
+    }
+    
+    // the last frame as it was presented, after post-processing and letterboxing; only headless engines keep it around
+    pub (crate) fn read_presented_frame(&self) -> Result<(Vec<u8>, (u32, u32)), String>
+    {
+        let frame = self.presented_frame.as_ref().ok_or_else(|| "error: no frame has been presented yet".to_string())?;
+        Ok((frame.read_rgba(), frame.dims))
     }
     
     pub (crate) fn render_finish(&mut self)
     {
-        let target = self.draw_target.as_mut().unwrap();
+        // headless engines never have a draw target, but their text still has to land in this frame
+        self.flush_text();
+        let source_dims = match &self.default_surface
+        {
+            Some(surface) => surface.dims,
+            None => return
+        };
+        if self.post_process.len() > 1 && self.post_targets.first().map(|surface| surface.dims) != Some(source_dims)
+        {
+            self.post_targets = vec!(Surface::new(&self.display, source_dims), Surface::new(&self.display, source_dims));
+        }
+        let error = match (self.draw_target.take(), &self.display)
+        {
+            (Some(mut target), _) =>
+            {
+                let error = self.present(&mut target);
+                target.set_finish().unwrap();
+                error
+            }
+            // headless engines present into a surface the size of their window instead, so that what they read back is post-processed and letterboxed too
+            (None, Backend::Headless(..)) =>
+            {
+                if self.presented_frame.as_ref().map(|surface| surface.dims) != Some(self.window_dims)
+                {
+                    self.presented_frame = Some(Surface::new(&self.display, self.window_dims));
+                }
+                let frame = self.presented_frame.as_ref().unwrap();
+                self.present(&mut frame.as_framebuffer(&self.display))
+            }
+            (None, Backend::Windowed(_)) => None
+        };
+        if error.is_some()
+        {
+            self.post_process_error = error;
+        }
+    }
+    
+    // runs the post-process passes on the default surface and draws the result to the window's size; returns the error of the last pass that failed
+    fn present(&self, target : &mut impl glium::Surface) -> Option<String>
+    {
+        let source_dims = self.default_surface.as_ref().unwrap().dims;
+        let time = duration_to_secs(&self.start_time.elapsed()) as f32;
         
-        let matrix_view = [
-            [1.0, 0.0, 0.0, 0.0],
-            [0.0, 1.0, 0.0, 0.0],
+        // the default surface covers the whole window, unless it has a virtual resolution and gets letterboxed
+        let (window_w, window_h) = (self.window_dims.0 as f64, self.window_dims.1 as f64);
+        let (left, top, w, h) = match self.virtual_resolution
+        {
+            Some((virtual_dims, mode)) =>
+            {
+                let color = self.letterbox_color;
+                target.clear_color_srgb(color[0], color[1], color[2], color[3]);
+                letterbox_rect(virtual_dims, self.window_dims, mode)
+            }
+            None => (0.0, 0.0, window_w, window_h)
+        };
+        let matrix_window = [
+            [(w/window_w*2.0) as f32, 0.0, 0.0, 0.0],
+            [0.0, (h/window_h*2.0) as f32, 0.0, 0.0],
             [0.0, 0.0, 1.0, 0.0],
-            [0.0, 0.0, 0.0, 1.0f32],
+            [(left/window_w*2.0 - 1.0) as f32, (1.0 - (top + h)/window_h*2.0) as f32, 0.0, 1.0f32],
         ];
-        let matrix_command = [
+        let matrix_surface = [
             [2.0, 0.0, 0.0, 0.0],
             [0.0, 2.0, 0.0, 0.0],
             [0.0, 0.0, 1.0, 0.0],
             [-1.0, -1.0, 0.0, 1.0f32],
         ];
         
-        let uniforms = uniform! {
-            matrix_view : matrix_view,
-            matrix_command : matrix_command,
-            tex_topleft : [0.0, 0.0f32],
-            tex_bottomright : [1.0, 1.0f32],
-            color_multiply : [1.0, 1.0, 1.0, 1.0f32],
-            tex : Sampler::new(&self.default_surface.as_ref().unwrap().rgba).minify_filter(MinifySamplerFilter::Nearest).magnify_filter(MagnifySamplerFilter::Nearest),
-        };
-        target.draw(&self.vertex_buffer, &self.indices, &self.current_program, &uniforms, &glium::DrawParameters
+        // every pass but the last draws into one of the two post-process surfaces, reading from the other one
+        // the final blit always uses the default program, so that programs set by scripts don't leak into it
+        // passes that fail to draw (like when a uniform has the wrong type) are skipped, and the error is kept for the script
+        let mut source = &self.default_surface.as_ref().unwrap().rgba;
+        let mut written = 0;
+        let mut presented = false;
+        let mut error = None;
+        let pass_count = self.post_process.len();
+        for (i, pass) in self.post_process.iter().enumerate()
         {
-            ..Default::default()
-        }).unwrap();
-        
-        target.set_finish().unwrap();
-        self.draw_target = None;
+            let program = self.programs.get(pass.program).unwrap_or(&self.default_program);
+            let last = i + 1 == pass_count;
+            let output_dims = if last { (w as f32, h as f32) } else { (source_dims.0 as f32, source_dims.1 as f32) };
+            let mut uniforms = fullscreen_uniforms(self.screen_sampler.apply(Sampler::new(source)), if last { matrix_window } else { matrix_surface });
+            uniforms.push("time", UniformValue::Float(time));
+            uniforms.push("resolution", UniformValue::Vec2([output_dims.0, output_dims.1]));
+            uniforms.push("source_resolution", UniformValue::Vec2([source_dims.0 as f32, source_dims.1 as f32]));
+            for (name, value) in &pass.uniforms
+            {
+                if let Some(value) = value.as_uniform_value(&self.sprites, &self.surfaces)
+                {
+                    uniforms.push(name, value);
+                }
+            }
+            
+            let result = if last
+            {
+                target.draw(&self.vertex_buffer, &self.indices, program, &uniforms, &Default::default())
+            }
+            else
+            {
+                let surface = &self.post_targets[written % 2];
+                surface.as_framebuffer(&self.display).draw(&self.vertex_buffer, &self.indices, program, &uniforms, &Default::default())
+            };
+            match result
+            {
+                Ok(()) if last => presented = true,
+                Ok(()) =>
+                {
+                    source = &self.post_targets[written % 2].rgba;
+                    written += 1;
+                }
+                Err(err) => error = Some(format!("error: post-process pass {} failed to draw: {:?}", pass.id, err))
+            }
+        }
+        if !presented
+        {
+            let uniforms = fullscreen_uniforms(self.screen_sampler.apply(Sampler::new(source)), matrix_window);
+            target.draw(&self.vertex_buffer, &self.indices, &self.default_program, &uniforms, &Default::default()).unwrap();
+        }
+        error
+    }
+    
+    // passes get ids that stay the same when the passes before them are removed
+    pub (super) fn push_post_process(&mut self, program_id : u64) -> Result<u64, String>
+    {
+        self.programs.lookup(program_id, "program")?;
+        let id = self.post_process_index_counter;
+        self.post_process_index_counter += 1;
+        self.post_process.push(PostProcessPass{id, program : program_id, uniforms : Vec::new()});
+        Ok(id)
+    }
+    pub (super) fn get_post_process_mut(&mut self, id : u64) -> Result<&mut PostProcessPass, String>
+    {
+        self.post_process.iter_mut().find(|pass| pass.id == id).ok_or_else(|| format!("error: no post-process pass with id {}", id))
+    }
+    pub (super) fn remove_post_process(&mut self, id : u64) -> Result<(), String>
+    {
+        let index = self.post_process.iter().position(|pass| pass.id == id).ok_or_else(|| format!("error: no post-process pass with id {}", id))?;
+        self.post_process.remove(index);
+        Ok(())
+    }
+    // post-processing happens after scripts are done with the frame, so draw errors are reported by the next post-process call instead
+    pub (super) fn check_post_process_error(&mut self) -> Result<(), String>
+    {
+        match self.post_process_error.take()
+        {
+            Some(err) => Err(err),
+            None => Ok(())
+        }
     }
 }
@@ -0,0 +1,163 @@
+// resources live in slots that get reused once they're freed
+// handles have the slot in their low 32 bits and the slot's generation in their high 32 bits, and freeing a resource bumps its slot's generation,
+// so that handles to freed resources are caught instead of referring to whatever gets loaded into the slot next
+// generations start at 1, so no handle is ever 0
+pub (super) struct ResourceStore<T> {
+    slots: Vec<(u32, Option<T>)>,
+    free: Vec<usize>,
+}
+
+fn split_handle(handle : u64) -> (usize, u32)
+{
+    ((handle & 0xFFFF_FFFF) as usize, (handle >> 32) as u32)
+}
+
+fn make_handle(slot : usize, generation : u32) -> u64
+{
+    (generation as u64) << 32 | slot as u64
+}
+
+impl<T> ResourceStore<T> {
+    pub (super) fn new() -> ResourceStore<T>
+    {
+        ResourceStore{slots : Vec::new(), free : Vec::new()}
+    }
+    pub (super) fn insert(&mut self, value : T) -> u64
+    {
+        match self.free.pop()
+        {
+            Some(slot) =>
+            {
+                self.slots[slot].1 = Some(value);
+                make_handle(slot, self.slots[slot].0)
+            }
+            None =>
+            {
+                self.slots.push((1, Some(value)));
+                make_handle(self.slots.len() - 1, 1)
+            }
+        }
+    }
+    pub (super) fn get(&self, handle : u64) -> Option<&T>
+    {
+        let (slot, generation) = split_handle(handle);
+        match self.slots.get(slot)
+        {
+            Some((current, value)) if *current == generation => value.as_ref(),
+            _ => None
+        }
+    }
+    pub (super) fn get_mut(&mut self, handle : u64) -> Option<&mut T>
+    {
+        let (slot, generation) = split_handle(handle);
+        match self.slots.get_mut(slot)
+        {
+            Some((current, value)) if *current == generation => value.as_mut(),
+            _ => None
+        }
+    }
+    pub (super) fn contains(&self, handle : u64) -> bool
+    {
+        self.get(handle).is_some()
+    }
+    // swaps in a new version of a resource that still exists, returning the old one
+    pub (super) fn replace(&mut self, handle : u64, value : T) -> Option<T>
+    {
+        self.get_mut(handle).map(|old| std::mem::replace(old, value))
+    }
+    pub (super) fn remove(&mut self, handle : u64) -> Option<T>
+    {
+        if !self.contains(handle)
+        {
+            return None;
+        }
+        let (slot, _) = split_handle(handle);
+        let (generation, value) = &mut self.slots[slot];
+        *generation = generation.wrapping_add(1).max(1);
+        self.free.push(slot);
+        value.take()
+    }
+    // every resource that hasn't been freed, in slot order
+    pub (super) fn values_mut(&mut self) -> impl Iterator<Item = &mut T>
+    {
+        self.slots.iter_mut().filter_map(|(_, value)| value.as_mut())
+    }
+    // like values_mut, along with each resource's handle
+    pub (super) fn iter_mut(&mut self) -> impl Iterator<Item = (u64, &mut T)>
+    {
+        self.slots.iter_mut().enumerate().filter_map(|(slot, (generation, value))| value.as_mut().map(|value| (make_handle(slot, *generation), value)))
+    }
+    // like get, but with an error that says whether the handle was freed or never existed; kind is the name of the resource type
+    pub (super) fn lookup(&self, handle : u64, kind : &str) -> Result<&T, String>
+    {
+        match self.get(handle)
+        {
+            Some(value) => Ok(value),
+            None => Err(self.missing(handle, kind))
+        }
+    }
+    pub (super) fn lookup_mut(&mut self, handle : u64, kind : &str) -> Result<&mut T, String>
+    {
+        if !self.contains(handle)
+        {
+            return Err(self.missing(handle, kind));
+        }
+        Ok(self.get_mut(handle).unwrap())
+    }
+    fn missing(&self, handle : u64, kind : &str) -> String
+    {
+        let (slot, generation) = split_handle(handle);
+        match self.slots.get(slot)
+        {
+            Some((current, _)) if generation != 0 && generation < *current => format!("error: {} was already freed", kind),
+            _ => format!("error: no such {}", kind)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_handles_are_generational()
+    {
+        let mut store = ResourceStore::new();
+        let a = store.insert("a");
+        let b = store.insert("b");
+        assert!(a != 0 && b != 0 && a != b);
+        assert_eq!(store.get(a), Some(&"a"));
+        assert_eq!(store.remove(a), Some("a"));
+        assert_eq!(store.remove(a), None);
+        assert_eq!(store.lookup(a, "sprite"), Err("error: sprite was already freed".to_string()));
+        // the freed slot gets reused, but the old handle still doesn't reach the new resource
+        let c = store.insert("c");
+        assert!(c != a);
+        assert_eq!(store.get(a), None);
+        assert_eq!(store.get(c), Some(&"c"));
+        assert_eq!(store.replace(a, "x"), None);
+        assert_eq!(store.replace(c, "d"), Some("c"));
+        assert_eq!(store.lookup(c, "sprite"), Ok(&"d"));
+        assert_eq!(store.lookup(12345, "sprite"), Err("error: no such sprite".to_string()));
+        assert_eq!(store.lookup(0, "sprite"), Err("error: no such sprite".to_string()));
+    }
+    
+    #[test]
+    fn test_values_mut_skips_freed()
+    {
+        let mut store = ResourceStore::new();
+        let a = store.insert(1);
+        let b = store.insert(2);
+        let c = store.insert(3);
+        store.remove(b);
+        for value in store.values_mut()
+        {
+            *value *= 10;
+        }
+        assert_eq!(store.get(a), Some(&10));
+        assert_eq!(store.values_mut().map(|value| *value).collect::<Vec<_>>(), vec!(10, 30));
+        // the new resource takes the freed slot, so it comes between the other two
+        let d = store.insert(4);
+        assert_eq!(store.iter_mut().map(|(handle, value)| (handle, *value)).collect::<Vec<_>>(), vec!((a, 10), (d, 4), (c, 30)));
+    }
+}
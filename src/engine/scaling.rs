@@ -0,0 +1,83 @@
+// how a fixed virtual resolution is fit into the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub (super) enum ScaleMode {
+    Integer,
+    Aspect,
+    Stretch,
+}
+
+impl ScaleMode {
+    pub (super) fn from_name(name : &str) -> Result<ScaleMode, String>
+    {
+        match name
+        {
+            "integer" => Ok(ScaleMode::Integer),
+            "aspect" => Ok(ScaleMode::Aspect),
+            "stretch" => Ok(ScaleMode::Stretch),
+            _ => Err(format!("error: unknown scaling mode `{}`; expected integer, aspect, or stretch", name))
+        }
+    }
+}
+
+// returns the rectangle, in window pixels from the top left, that the virtual screen is drawn into; the rest is letterboxed
+// integer scaling falls back to aspect fitting when the window is smaller than the virtual resolution
+pub (super) fn letterbox_rect(virtual_dims : (u32, u32), window_dims : (u32, u32), mode : ScaleMode) -> (f64, f64, f64, f64)
+{
+    let (vw, vh) = (virtual_dims.0 as f64, virtual_dims.1 as f64);
+    let (ww, wh) = (window_dims.0 as f64, window_dims.1 as f64);
+    let fit = (ww/vw).min(wh/vh);
+    let (w, h) = match mode
+    {
+        ScaleMode::Stretch => (ww, wh),
+        ScaleMode::Aspect => ((vw*fit).round(), (vh*fit).round()),
+        ScaleMode::Integer =>
+        {
+            let scale = if fit >= 1.0 { fit.floor() } else { fit };
+            ((vw*scale).round(), (vh*scale).round())
+        }
+    };
+    (((ww - w)/2.0).floor(), ((wh - h)/2.0).floor(), w, h)
+}
+
+// maps a position in window pixels to a position on the virtual screen
+// a minimized window has a rect with no area, and positions in it all map to the top left
+pub (crate) fn window_to_virtual(rect : (f64, f64, f64, f64), virtual_dims : (u32, u32), (x, y) : (f64, f64)) -> (f64, f64)
+{
+    let (left, top, w, h) = rect;
+    if !(w > 0.0 && h > 0.0)
+    {
+        return (0.0, 0.0);
+    }
+    ((x - left)*virtual_dims.0 as f64/w, (y - top)*virtual_dims.1 as f64/h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_scaling()
+    {
+        assert_eq!(letterbox_rect((320, 180), (1000, 600), ScaleMode::Integer), (20.0, 30.0, 960.0, 540.0));
+        // too small for even 1x
+        assert_eq!(letterbox_rect((320, 180), (160, 180), ScaleMode::Integer), (0.0, 45.0, 160.0, 90.0));
+    }
+
+    #[test]
+    fn test_aspect_and_stretch()
+    {
+        assert_eq!(letterbox_rect((320, 180), (1000, 600), ScaleMode::Aspect), (0.0, 18.0, 1000.0, 563.0));
+        assert_eq!(letterbox_rect((320, 180), (1000, 600), ScaleMode::Stretch), (0.0, 0.0, 1000.0, 600.0));
+    }
+
+    #[test]
+    fn test_window_to_virtual()
+    {
+        let rect = letterbox_rect((320, 180), (1000, 600), ScaleMode::Integer);
+        assert_eq!(window_to_virtual(rect, (320, 180), (20.0, 30.0)), (0.0, 0.0));
+        assert_eq!(window_to_virtual(rect, (320, 180), (980.0, 570.0)), (320.0, 180.0));
+        assert_eq!(window_to_virtual(rect, (320, 180), (500.0, 300.0)), (160.0, 90.0));
+        let rect = letterbox_rect((320, 180), (0, 0), ScaleMode::Aspect);
+        assert_eq!(window_to_virtual(rect, (320, 180), (10.0, 10.0)), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,105 @@
+// one message from a shader compiler or linker log, tied to the file it came from
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct ShaderMessage {
+    pub (super) file: String,
+    pub (super) line: Option<u32>,
+    pub (super) message: String,
+}
+
+impl ShaderMessage {
+    pub (super) fn new(file : &str, line : Option<u32>, message : &str) -> ShaderMessage
+    {
+        ShaderMessage{file : file.to_string(), line, message : message.to_string()}
+    }
+    pub (super) fn format(&self) -> String
+    {
+        match self.line
+        {
+            Some(line) => format!("{}:{}: {}", self.file, line, self.message),
+            None => format!("{}: {}", self.file, self.message)
+        }
+    }
+}
+
+fn parse_number(text : &str) -> Option<u32>
+{
+    text.trim().parse::<u32>().ok()
+}
+
+// drivers disagree on how they format their logs; these are the common ones, where the first number is the source string and the second is the line
+//   mesa:   0:12(5): error: `foo' undeclared
+//   nvidia: 0(12) : error C1008: undefined variable "foo"
+//   amd:    ERROR: 0:12: 'foo' : undeclared identifier
+fn parse_log_line(text : &str) -> Option<(u32, String)>
+{
+    let text = text.trim();
+    let text = text.strip_prefix("ERROR:").or_else(|| text.strip_prefix("WARNING:")).map(|rest| rest.trim_start()).unwrap_or(text);
+    
+    let digits = text.find(|c : char| !c.is_ascii_digit())?;
+    if digits == 0
+    {
+        return None;
+    }
+    let rest = &text[digits..];
+    if let Some(rest) = rest.strip_prefix('(')
+    {
+        let close = rest.find(')')?;
+        let line = parse_number(&rest[..close])?;
+        let message = rest[close + 1..].trim_start().strip_prefix(':')?;
+        return Some((line, message.trim().to_string()));
+    }
+    let rest = rest.strip_prefix(':')?;
+    let end = rest.find(|c : char| !c.is_ascii_digit())?;
+    let line = parse_number(&rest[..end])?;
+    let mut message = &rest[end..];
+    // mesa also gives the column
+    if message.starts_with('(')
+    {
+        message = &message[message.find(')')? + 1..];
+    }
+    let message = message.trim_start().strip_prefix(':')?;
+    Some((line, message.trim().to_string()))
+}
+
+// lines that don't look like any known format are kept whole, without a line number
+pub (super) fn parse_shader_log(file : &str, log : &str) -> Vec<ShaderMessage>
+{
+    let messages : Vec<ShaderMessage> = log.lines().filter(|text| !text.trim().is_empty()).map(|text|
+    {
+        match parse_log_line(text)
+        {
+            Some((line, message)) => ShaderMessage::new(file, Some(line), &message),
+            None => ShaderMessage::new(file, None, text.trim())
+        }
+    }).collect();
+    if messages.is_empty()
+    {
+        return vec!(ShaderMessage::new(file, None, "failed without a log"));
+    }
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_driver_formats()
+    {
+        assert_eq!(parse_log_line("0:12(5): error: `foo' undeclared"), Some((12, "error: `foo' undeclared".to_string())));
+        assert_eq!(parse_log_line("0(7) : error C1008: undefined variable \"foo\""), Some((7, "error C1008: undefined variable \"foo\"".to_string())));
+        assert_eq!(parse_log_line("ERROR: 0:3: 'foo' : undeclared identifier"), Some((3, "'foo' : undeclared identifier".to_string())));
+        assert_eq!(parse_log_line("error: linking failed"), None);
+    }
+    
+    #[test]
+    fn test_parse_shader_log()
+    {
+        let log = "0:4(1): error: syntax error, unexpected '}'\n\nsomething else went wrong\n";
+        let messages = parse_shader_log("glsl/fragment.glsl", log);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].format(), "glsl/fragment.glsl:4: error: syntax error, unexpected '}'");
+        assert_eq!(messages[1], ShaderMessage::new("glsl/fragment.glsl", None, "something else went wrong"));
+        assert_eq!(parse_shader_log("a.glsl", "").len(), 1);
+    }
+}
@@ -0,0 +1,182 @@
+use serde_json::Value as Json;
+
+use super::render::{SpriteImage, SpriteTag};
+
+pub (super) struct SheetData {
+    pub (super) image: Option<String>,
+    pub (super) images: Vec<SpriteImage>,
+    pub (super) tags: Vec<SpriteTag>,
+}
+
+// slices a texture into a uniform grid of frames, left to right then top to bottom
+pub (super) fn grid_images(texture_dims : (u32, u32), frame_dims : (f64, f64), count : usize, origin : (f64, f64)) -> Result<Vec<SpriteImage>, String>
+{
+    let (frame_w, frame_h) = frame_dims;
+    if frame_w < 1.0 || frame_h < 1.0
+    {
+        return Err("error: sprite strip frames must be at least 1x1 pixels in size".to_string());
+    }
+    let columns = (texture_dims.0 as f64 / frame_w).floor() as usize;
+    let rows = (texture_dims.1 as f64 / frame_h).floor() as usize;
+    if count == 0 || count > columns*rows
+    {
+        return Err(format!("error: sprite strip asked for {} frames but the image only fits {}", count, columns*rows));
+    }
+    Ok((0..count).map(|i|
+    {
+        let x = (i % columns) as f64 * frame_w;
+        let y = (i / columns) as f64 * frame_h;
+        SpriteImage::extended(origin, (x, y), (x + frame_w, y + frame_h))
+    }).collect())
+}
+
+fn get_number(value : &Json, key : &str) -> Result<f64, String>
+{
+    value[key].as_f64().ok_or_else(|| format!("error: sprite sheet entry is missing number `{}`", key))
+}
+
+// reads the json exported by aseprite or texturepacker, in either their hash or array layout
+// origin is measured from the top left of each untrimmed frame, unless the frame has its own pivot
+pub (super) fn parse_sheet_json(text : &str, origin : (f64, f64)) -> Result<SheetData, String>
+{
+    let root : Json = serde_json::from_str(text).or_else(|err| Err(format!("error: failed to parse sprite sheet json: {}", err)))?;
+    
+    let frames : Vec<&Json> = match &root["frames"]
+    {
+        Json::Array(list) => list.iter().collect(),
+        Json::Object(map) => map.values().collect(),
+        _ => return Err("error: sprite sheet json has no `frames` list".to_string())
+    };
+    
+    let mut images = Vec::new();
+    for frame in frames
+    {
+        if frame["rotated"].as_bool() == Some(true)
+        {
+            return Err("error: rotated sprite sheet frames are not supported".to_string());
+        }
+        let rect = &frame["frame"];
+        let (x, y) = (get_number(rect, "x")?, get_number(rect, "y")?);
+        let (w, h) = (get_number(rect, "w")?, get_number(rect, "h")?);
+        
+        let (trim_x, trim_y) = if frame["trimmed"].as_bool() == Some(true)
+        {
+            (get_number(&frame["spriteSourceSize"], "x")?, get_number(&frame["spriteSourceSize"], "y")?)
+        }
+        else
+        {
+            (0.0, 0.0)
+        };
+        let (source_w, source_h) = match frame["sourceSize"].is_object()
+        {
+            true => (get_number(&frame["sourceSize"], "w")?, get_number(&frame["sourceSize"], "h")?),
+            false => (w, h)
+        };
+        let frame_origin = match frame["pivot"].is_object()
+        {
+            true => (get_number(&frame["pivot"], "x")? * source_w, get_number(&frame["pivot"], "y")? * source_h),
+            false => origin
+        };
+        // aseprite durations are in milliseconds
+        let duration = frame["duration"].as_f64().map(|ms| ms / 1000.0);
+        
+        images.push(SpriteImage::extended((frame_origin.0 - trim_x, frame_origin.1 - trim_y), (x, y), (x + w, y + h)).with_duration(duration));
+    }
+    if images.is_empty()
+    {
+        return Err("error: sprite sheet json has no frames".to_string());
+    }
+    
+    let mut tags = Vec::new();
+    if let Json::Array(list) = &root["meta"]["frameTags"]
+    {
+        for tag in list
+        {
+            let name = tag["name"].as_str().ok_or_else(|| "error: sprite sheet frame tag is missing its name".to_string())?.to_string();
+            let from = get_number(tag, "from")? as usize;
+            let to = get_number(tag, "to")? as usize;
+            if from > to || to >= images.len()
+            {
+                return Err(format!("error: sprite sheet frame tag `{}` is out of range", name));
+            }
+            let direction = tag["direction"].as_str().unwrap_or("forward").to_string();
+            tags.push(SpriteTag{name, from, to, direction});
+        }
+    }
+    
+    let image = root["meta"]["image"].as_str().map(|x| x.to_string());
+    
+    Ok(SheetData{image, images, tags})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_grid_images()
+    {
+        let images = grid_images((64, 48), (16.0, 16.0), 6, (8.0, 8.0)).unwrap();
+        assert_eq!(images.len(), 6);
+        assert_eq!(images[3].topleft, (48.0, 0.0));
+        assert_eq!(images[4].topleft, (0.0, 16.0));
+        assert_eq!(images[5].bottomright, (32.0, 32.0));
+        assert!(grid_images((64, 48), (16.0, 16.0), 13, (0.0, 0.0)).is_err());
+    }
+    
+    #[test]
+    fn test_aseprite_hash()
+    {
+        let text = r#"{
+            "frames": {
+                "walk 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 100 },
+                "walk 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 250 },
+                "walk 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 100 }
+            },
+            "meta": {
+                "image": "walk.png",
+                "frameTags": [
+                    { "name": "idle", "from": 0, "to": 0, "direction": "forward" },
+                    { "name": "walk", "from": 1, "to": 2, "direction": "pingpong" }
+                ]
+            }
+        }"#;
+        let sheet = parse_sheet_json(text, (8.0, 16.0)).unwrap();
+        assert_eq!(sheet.image, Some("walk.png".to_string()));
+        assert_eq!(sheet.images.len(), 3);
+        assert_eq!(sheet.images[1].topleft, (16.0, 0.0));
+        assert_eq!(sheet.images[1].origin, (8.0, 16.0));
+        assert_eq!(sheet.images[1].duration, Some(0.25));
+        assert_eq!(sheet.tags[1], SpriteTag{name : "walk".to_string(), from : 1, to : 2, direction : "pingpong".to_string()});
+    }
+    
+    #[test]
+    fn test_texturepacker_array_trimmed_pivot()
+    {
+        let text = r#"{
+            "frames": [
+                {
+                    "filename": "hero.png",
+                    "frame": { "x": 2, "y": 2, "w": 20, "h": 30 },
+                    "rotated": false,
+                    "trimmed": true,
+                    "spriteSourceSize": { "x": 6, "y": 2, "w": 20, "h": 30 },
+                    "sourceSize": { "w": 32, "h": 32 },
+                    "pivot": { "x": 0.5, "y": 1.0 }
+                }
+            ],
+            "meta": { "image": "atlas.png" }
+        }"#;
+        let sheet = parse_sheet_json(text, (0.0, 0.0)).unwrap();
+        assert_eq!(sheet.images[0].origin, (10.0, 30.0));
+        assert_eq!(sheet.images[0].duration, None);
+        assert!(sheet.tags.is_empty());
+    }
+    
+    #[test]
+    fn test_rotated_frames_rejected()
+    {
+        let text = r#"{ "frames": [ { "frame": { "x": 0, "y": 0, "w": 4, "h": 4 }, "rotated": true } ] }"#;
+        assert!(parse_sheet_json(text, (0.0, 0.0)).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+// the top left and bottom right corners of a rectangle
+type Corners = ((f32, f32), (f32, f32));
+
+// splits laid out quads into the runs that belong to each queued section, given the section of every quad in order
+// quads from one section are always next to each other, so each run is drawn with that section's state in one call
+pub (super) fn section_runs(sections : &[usize]) -> Vec<(usize, Range<usize>)>
+{
+    let mut runs : Vec<(usize, Range<usize>)> = Vec::new();
+    for (index, section) in sections.iter().enumerate()
+    {
+        match runs.last_mut()
+        {
+            Some((current, range)) if current == section => range.end = index + 1,
+            _ => runs.push((*section, index..index + 1))
+        }
+    }
+    runs
+}
+
+// the corners of a glyph quad as two triangles, each corner being a position in pixels and a texture coordinate
+pub (super) fn quad_corners(min : (f32, f32), max : (f32, f32), tex_min : (f32, f32), tex_max : (f32, f32)) -> [([f32; 2], [f32; 2]); 6]
+{
+    let top_left = ([min.0, min.1], [tex_min.0, tex_min.1]);
+    let top_right = ([max.0, min.1], [tex_max.0, tex_min.1]);
+    let bottom_left = ([min.0, max.1], [tex_min.0, tex_max.1]);
+    let bottom_right = ([max.0, max.1], [tex_max.0, tex_max.1]);
+    [top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]
+}
+
+// a glyph quad grown by some pixels on every side, with its texture coordinates grown along with it, for drawing outlines
+// the texture past the glyph's own rect belongs to other glyphs, so the outline shader doesn't sample there
+pub (super) fn grown_quad(min : (f32, f32), max : (f32, f32), tex_min : (f32, f32), tex_max : (f32, f32), grow : f32) -> (Corners, Corners)
+{
+    let per_pixel = |size : f32, tex_size : f32| if size > 0.0 { tex_size/size } else { 0.0 };
+    let tex_grow = (per_pixel(max.0 - min.0, tex_max.0 - tex_min.0)*grow, per_pixel(max.1 - min.1, tex_max.1 - tex_min.1)*grow);
+    let draw = ((min.0 - grow, min.1 - grow), (max.0 + grow, max.1 + grow));
+    let tex = ((tex_min.0 - tex_grow.0, tex_min.1 - tex_grow.1), (tex_max.0 + tex_grow.0, tex_max.1 + tex_grow.1));
+    (draw, tex)
+}
+
+// glyph_brush's rule for which glyphs get a quad: those that aren't completely outside of their section's bounds, given as top left and bottom right corners
+pub (super) fn quad_in_bounds(min : (i32, i32), max : (i32, i32), bounds : Corners) -> bool
+{
+    let ((left, top), (right, bottom)) = bounds;
+    !(min.0 as f32 > right || min.1 as f32 > bottom || left > max.0 as f32 || top > max.1 as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_section_runs()
+    {
+        assert_eq!(section_runs(&[0, 0, 0, 1, 1, 2]), vec!((0, 0..3), (1, 3..5), (2, 5..6)));
+        // sections with no quads, like empty strings, don't get a run
+        assert_eq!(section_runs(&[0, 2, 2, 5]), vec!((0, 0..1), (2, 1..3), (5, 3..4)));
+        assert_eq!(section_runs(&[3]), vec!((3, 0..1)));
+        assert!(section_runs(&[]).is_empty());
+    }
+    #[test]
+    fn test_quad_corners()
+    {
+        let corners = quad_corners((10.0, 20.0), (14.0, 28.0), (0.5, 0.25), (0.75, 0.5));
+        assert_eq!(corners[0], ([10.0, 20.0], [0.5, 0.25]));
+        assert_eq!(corners[5], ([14.0, 28.0], [0.75, 0.5]));
+        // both triangles share the top right and bottom left corners
+        assert_eq!(corners[2], corners[3]);
+        assert_eq!(corners[1], corners[4]);
+        assert_eq!(corners[2], ([14.0, 20.0], [0.75, 0.25]));
+    }
+    #[test]
+    fn test_grown_quad()
+    {
+        // 4 pixels of width cover a quarter of the texture's width, so 2 pixels are an eighth
+        let (draw, tex) = grown_quad((10.0, 20.0), (14.0, 28.0), (0.5, 0.25), (0.75, 0.5), 2.0);
+        assert_eq!(draw, ((8.0, 18.0), (16.0, 30.0)));
+        assert_eq!(tex, ((0.375, 0.1875), (0.875, 0.5625)));
+        assert_eq!(grown_quad((1.0, 1.0), (2.0, 2.0), (0.0, 0.0), (0.5, 0.5), 0.0), (((1.0, 1.0), (2.0, 2.0)), ((0.0, 0.0), (0.5, 0.5))));
+    }
+    #[test]
+    fn test_quad_in_bounds()
+    {
+        let bounds = ((0.0, 0.0), (100.0, 20.0));
+        assert!(quad_in_bounds((10, 5), (18, 15), bounds));
+        // glyphs that stick out of the bounds still get a quad, as long as some of them is inside
+        assert!(quad_in_bounds((95, 15), (105, 25), bounds));
+        assert!(quad_in_bounds((-5, 0), (0, 10), bounds));
+        assert!(!quad_in_bounds((10, 21), (18, 31), bounds));
+        assert!(!quad_in_bounds((101, 0), (110, 10), bounds));
+    }
+}
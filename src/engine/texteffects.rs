@@ -0,0 +1,144 @@
+use super::markup::MarkupSpan;
+
+// settings for the per-glyph effects that markup turns on; amplitudes are in pixels
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct TextEffects {
+    pub (super) wave_amplitude: f32,
+    pub (super) wave_frequency: f32,
+    pub (super) wave_speed: f32,
+    pub (super) shake_amplitude: f32,
+    pub (super) shake_rate: f32,
+}
+
+// copies of every glyph drawn behind it in flat colors; outlines are widths in pixels, and shadows are offsets
+#[derive(Debug, Clone, PartialEq)]
+pub (super) struct TextDecoration {
+    pub (super) outline: Option<(f32, [f32; 4])>,
+    pub (super) shadow: Option<((f32, f32), [f32; 4])>,
+}
+
+impl TextDecoration {
+    pub (super) fn new() -> TextDecoration
+    {
+        TextDecoration{outline : None, shadow : None}
+    }
+    // the offset, radius in pixels, and color of each copy, in the order they're drawn; the shadow goes under the outline
+    // copies are grown by their radius when they're drawn, so an outline is one copy, which can't have gaps or stack translucent colors
+    pub (super) fn passes(&self) -> Vec<((f32, f32), f32, [f32; 4])>
+    {
+        let mut passes = Vec::new();
+        if let Some((offset, color)) = self.shadow
+        {
+            passes.push((offset, 0.0, color));
+        }
+        if let Some((width, color)) = self.outline
+        {
+            if width > 0.0
+            {
+                passes.push(((0.0, 0.0), width, color));
+            }
+        }
+        passes
+    }
+}
+
+// the (wave, shake) flags of each char of the spans' text; glyphs look theirs up by the char they were made from
+pub (super) fn span_char_effects(spans : &[MarkupSpan]) -> Vec<(bool, bool)>
+{
+    spans.iter().flat_map(|span| std::iter::repeat((span.wave, span.shake)).take(span.text.chars().count())).collect()
+}
+
+// cheap integer hash, so that shaking is random-looking but the same for every glyph drawn in the same step
+fn hash(mut x : u32) -> u32
+{
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
+
+fn hash_unit(x : u32) -> f32
+{
+    (hash(x) % 20001) as f32/10000.0 - 1.0
+}
+
+impl TextEffects {
+    pub (super) fn new() -> TextEffects
+    {
+        TextEffects{wave_amplitude : 3.0, wave_frequency : 0.6, wave_speed : 6.0, shake_amplitude : 1.5, shake_rate : 20.0}
+    }
+    // returns the offset and alpha multiplier of one glyph, or None if it hasn't been revealed yet
+    // glyphs fade in over their own step of the reveal count, so fractional counts give a smooth typewriter
+    pub (super) fn glyph_transform(&self, index : usize, (wave, shake) : (bool, bool), time : f32, reveal : f32) -> Option<(f32, f32, f32)>
+    {
+        let alpha = (reveal - index as f32).min(1.0);
+        if !(alpha > 0.0)
+        {
+            return None;
+        }
+        let (mut x, mut y) = (0.0, 0.0);
+        if wave
+        {
+            y += (time*self.wave_speed - index as f32*self.wave_frequency).sin()*self.wave_amplitude;
+        }
+        if shake
+        {
+            let step = (time*self.shake_rate).floor() as u32;
+            let seed = hash(index as u32).wrapping_add(step.wrapping_mul(2));
+            x += hash_unit(seed)*self.shake_amplitude;
+            y += hash_unit(seed.wrapping_add(1))*self.shake_amplitude;
+        }
+        Some((x, y, alpha))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::markup::parse_markup;
+    
+    #[test]
+    fn test_char_effects_follow_spans()
+    {
+        let spans = parse_markup("a [wave]cd[/wave] [shake]e[/shake]");
+        assert_eq!(span_char_effects(&spans), vec!((false, false), (false, false), (true, false), (true, false), (false, false), (false, true)));
+    }
+    
+    #[test]
+    fn test_decoration_passes()
+    {
+        let mut decoration = TextDecoration::new();
+        assert!(decoration.passes().is_empty());
+        decoration.shadow = Some(((2.0, 2.0), [0.0, 0.0, 0.0, 0.5]));
+        decoration.outline = Some((1.0, [0.0, 0.0, 0.0, 1.0]));
+        assert_eq!(decoration.passes(), vec!(((2.0, 2.0), 0.0, [0.0, 0.0, 0.0, 0.5]), ((0.0, 0.0), 1.0, [0.0, 0.0, 0.0, 1.0])));
+        // wide outlines are still a single copy
+        decoration.shadow = None;
+        decoration.outline = Some((12.0, [0.0, 0.0, 0.0, 0.5]));
+        assert_eq!(decoration.passes(), vec!(((0.0, 0.0), 12.0, [0.0, 0.0, 0.0, 0.5])));
+    }
+    
+    #[test]
+    fn test_reveal()
+    {
+        let effects = TextEffects::new();
+        assert_eq!(effects.glyph_transform(0, (false, false), 0.0, 0.0), None);
+        assert_eq!(effects.glyph_transform(2, (false, false), 0.0, 2.5), Some((0.0, 0.0, 0.5)));
+        assert_eq!(effects.glyph_transform(1, (false, false), 0.0, 2.5), Some((0.0, 0.0, 1.0)));
+        assert_eq!(effects.glyph_transform(3, (false, false), 0.0, 2.5), None);
+    }
+    
+    #[test]
+    fn test_shake_is_bounded_and_stable()
+    {
+        let effects = TextEffects::new();
+        for index in 0..50
+        {
+            let (x, y, _) = effects.glyph_transform(index, (false, true), 1.0, 100.0).unwrap();
+            assert!(x.abs() <= effects.shake_amplitude && y.abs() <= effects.shake_amplitude);
+            assert_eq!(effects.glyph_transform(index, (false, true), 1.01, 100.0), Some((x, y, 1.0)));
+        }
+    }
+}
@@ -3,10 +3,14 @@ use std::collections::HashMap;
 use glium::glutin;
 use glutin::{MouseButton, ElementState, MouseScrollDelta};
 
+use crate::engine::scaling::window_to_virtual;
+
 pub (crate) struct InputHandler {
     pub (crate) keys_down_previous: HashMap<String, bool>,
     pub (crate) keys_down: HashMap<String, bool>,
     pub (crate) mouse_pos: (f64, f64),
+    pub (crate) mouse_pos_window: (f64, f64),
+    pub (crate) mouse_transform: Option<((f64, f64, f64, f64), (u32, u32))>, // letterbox rect and virtual resolution, for mapping window pixels to screen pixels
     pub (crate) mouse_delta: (f64, f64),
     pub (crate) mouse_buttons_previous: [bool; 5],
     pub (crate) mouse_buttons: [bool; 5],
@@ -21,6 +25,8 @@ impl InputHandler {
             keys_down : HashMap::new(),
             keys_down_previous : HashMap::new(),
             mouse_pos: (0.0, 0.0),
+            mouse_pos_window: (0.0, 0.0),
+            mouse_transform: None,
             mouse_delta: (0.0, 0.0),
             mouse_buttons_previous: [false, false, false, false, false],
             mouse_buttons: [false, false, false, false, false],
@@ -165,6 +171,15 @@ impl InputHandler {
             }
         }
     }
+    pub (crate) fn set_mouse_pos(&mut self, pos : (f64, f64))
+    {
+        self.mouse_pos_window = pos;
+        self.mouse_pos = match self.mouse_transform
+        {
+            Some((rect, virtual_dims)) => window_to_virtual(rect, virtual_dims, pos),
+            None => pos
+        };
+    }
     pub (crate) fn mousebuttonevent(&mut self, state : ElementState, button : MouseButton)
     {
         let state = match state
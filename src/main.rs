@@ -31,13 +31,63 @@ fn open_file(root : &String, prefix : &String, fname : &str) -> Result<BufReader
     }
 }
 
+// the path that open_file would read from, for watching files for changes
+fn find_file(root : &String, prefix : &String, fname : &str) -> Option<std::path::PathBuf>
+{
+    let candidates = [format!("{}/{}/{}", root, prefix, fname), format!("{}/{}", prefix, fname)];
+    candidates.iter().map(std::path::PathBuf::from).find(|path| path.is_file())
+}
+
+// files written by scripts go next to the data they were loaded from, preferring the program's directory like open_file does
+fn output_path(root : &String, prefix : &String, fname : &str) -> String
+{
+    if std::path::Path::new(&format!("{}/{}", root, prefix)).is_dir()
+    {
+        format!("{}/{}/{}", root, prefix, fname)
+    }
+    else
+    {
+        format!("{}/{}", prefix, fname)
+    }
+}
+
 fn load_string(root : &String, prefix : &String, fname : &str) -> Result<String, String>
 {
     let mut file = open_file(root, prefix, fname)?;
     return read_string(&mut file, fname);
 }
 
-fn launch_from_path(prefix : &str) -> Result<(), String>
+// headless runs draw a fixed number of frames without a window, then read back the last frame as it would have been presented
+struct HeadlessOptions {
+    dims : (u32, u32),
+    frames : u64,
+    output : Option<String>,
+}
+
+fn build_headless_backend(dims : (u32, u32)) -> Result<Backend, String>
+{
+    use glium::glutin;
+    
+    let size = glutin::dpi::PhysicalSize::new(dims.0 as f64, dims.1 as f64);
+    let builder = glutin::ContextBuilder::new();
+    // on unix, osmesa lets us render on Mesa's software rasterizer without a display server
+    #[cfg(unix)]
+    let context =
+    {
+        use glutin::os::unix::HeadlessContextExt;
+        builder.build_osmesa(size).or_else(|err| Err(format!("error: failed to create headless context: {}", err)))?
+    };
+    #[cfg(not(unix))]
+    let context =
+    {
+        let events_loop = glutin::EventsLoop::new();
+        builder.build_headless(&events_loop, size).or_else(|err| Err(format!("error: failed to create headless context: {}", err)))?
+    };
+    let renderer = glium::HeadlessRenderer::new(context).or_else(|err| Err(format!("error: failed to create headless renderer: {:?}", err)))?;
+    Ok(Backend::Headless(renderer, dims))
+}
+
+fn launch_from_path(prefix : &str, headless : Option<HeadlessOptions>) -> Result<(), String>
 {
     use glium::glutin;
     
@@ -48,14 +98,24 @@ fn launch_from_path(prefix : &str) -> Result<(), String>
     println!("running from path `{}` with prefix `{}`", program_path, prefix);
     let prefix = prefix.to_string();
     
-    let mut events_loop = glutin::EventsLoop::new();
-    let window = glutin::WindowBuilder::new().with_dimensions(glutin::dpi::LogicalSize::new(800.0, 600.0));
-    let context = glutin::ContextBuilder::new().with_vsync(false);
-    let display = glium::Display::new(window, context, &events_loop).unwrap();
+    let mut events_loop = None;
+    let backend = if let Some(options) = &headless
+    {
+        build_headless_backend(options.dims)?
+    }
+    else
+    {
+        let new_events_loop = glutin::EventsLoop::new();
+        let window = glutin::WindowBuilder::new().with_dimensions(glutin::dpi::LogicalSize::new(800.0, 600.0));
+        let context = glutin::ContextBuilder::new().with_vsync(false);
+        let display = glium::Display::new(window, context, &new_events_loop).unwrap();
+        events_loop = Some(new_events_loop);
+        Backend::Windowed(display)
+    };
     
     use std::rc::Rc;
     use std::cell::RefCell;
-    let engine = Rc::new(RefCell::new(Engine::load(display, program_path.clone(), prefix.clone())));
+    let engine = Rc::new(RefCell::new(Engine::load(backend, program_path.clone(), prefix.clone())));
     
     use gammakit::Interpreter;
     let mut interpreter = Interpreter::new(gammakit::Parser::new_from_default().unwrap());
@@ -79,41 +139,55 @@ fn launch_from_path(prefix : &str) -> Result<(), String>
     run_interpreter!();
     
     let mut closed = false;
+    let mut frames_run = 0;
     
     while !closed
     {
+        if let Some(options) = &headless
+        {
+            if frames_run >= options.frames
+            {
+                break;
+            }
+        }
+        frames_run += 1;
+        
         if let Ok(mut engine) = engine.try_borrow_mut()
         {
             engine.check_init_framerate_limiter();
+            engine.poll_hot_reload();
             
             engine.input_handler.cycle();
             
-            events_loop.poll_events(|event|
+            if let Some(events_loop) = events_loop.as_mut()
             {
-                use glium::glutin::{Event::WindowEvent, WindowEvent::*, Event::DeviceEvent, DeviceEvent::MouseMotion};
-                match event
+                events_loop.poll_events(|event|
                 {
-                    WindowEvent{event, ..} => match event
-                    {
-                        CloseRequested => closed = true,
-                        KeyboardInput{input, ..} => engine.input_handler.keyevent(input),
-                        MouseInput{state, button, ..} => engine.input_handler.mousebuttonevent(state, button),
-                        //CursorMoved{position, ..} => engine.input_handler.mouse_pos = position.into(),
-                        MouseWheel{delta, ..} => engine.input_handler.scroll(delta),
-                        _ => ()
-                    },
-                    DeviceEvent{event, ..} => match event
+                    use glium::glutin::{Event::WindowEvent, WindowEvent::*, Event::DeviceEvent, DeviceEvent::MouseMotion};
+                    match event
                     {
-                        MouseMotion{delta, ..} =>
+                        WindowEvent{event, ..} => match event
+                        {
+                            CloseRequested => closed = true,
+                            KeyboardInput{input, ..} => engine.input_handler.keyevent(input),
+                            MouseInput{state, button, ..} => engine.input_handler.mousebuttonevent(state, button),
+                            //CursorMoved{position, ..} => engine.input_handler.mouse_pos = position.into(),
+                            MouseWheel{delta, ..} => engine.input_handler.scroll(delta),
+                            _ => ()
+                        },
+                        DeviceEvent{event, ..} => match event
                         {
-                            engine.input_handler.mouse_delta.0 += delta.0;
-                            engine.input_handler.mouse_delta.1 += delta.1;
+                            MouseMotion{delta, ..} =>
+                            {
+                                engine.input_handler.mouse_delta.0 += delta.0;
+                                engine.input_handler.mouse_delta.1 += delta.1;
+                            }
+                            _ => ()
                         }
-                        _ => ()
+                        _ => (),
                     }
-                    _ => (),
-                }
-            });
+                });
+            }
             engine.unsafe_check_global_cursor_position();
         }
         else
@@ -139,25 +213,66 @@ fn launch_from_path(prefix : &str) -> Result<(), String>
         if let Ok(mut engine) = engine.try_borrow_mut()
         {
             engine.render_finish();
-            engine.cycle_framerate_limiter();
+            // headless runs are not paced, so every frame sees the target frame time as its delta
+            if headless.is_none()
+            {
+                engine.cycle_framerate_limiter();
+            }
+            engine.advance_animations();
+            engine.advance_frame_time();
         }
         else
         {
             panic!("error: failed to lock engine in mainloop");
         }
     }
+    
+    if let Some(HeadlessOptions{output : Some(output), ..}) = &headless
+    {
+        let (pixels, (w, h)) = engine.try_borrow().or_else(|_| Err("error: failed to lock engine after headless run".to_string()))?.read_presented_frame()?;
+        image::save_buffer(output, &pixels, w, h, image::ColorType::RGBA(8)).or_else(|err| Err(format!("error: failed to save `{}`: {}", output, err)))?;
+    }
     Ok(())
 }
 
+// usage: magmakit [prefix] [--headless <frames> <width> <height> [output.png]]
 fn main() -> Result<(), String>
 {
     let args: Vec<_> = std::env::args().collect();
-    if args.len() > 1
+    let prefix = args.get(1).map(|x| x.as_str()).unwrap_or("data");
+    
+    if args.get(2).map(|x| x.as_str()) == Some("--headless")
     {
-        launch_from_path(&args[1])
+        let number = |index : usize| -> Result<u64, String>
+        {
+            args.get(index).and_then(|x| x.parse::<u64>().ok()).ok_or_else(|| "error: usage: magmakit <prefix> --headless <frames> <width> <height> [output.png]".to_string())
+        };
+        let options = HeadlessOptions
+        {
+            frames : number(3)?,
+            dims : (number(4)? as u32, number(5)? as u32),
+            output : args.get(6).cloned(),
+        };
+        launch_from_path(prefix, Some(options))
     }
     else
     {
-        launch_from_path("data")
+        launch_from_path(prefix, None)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // needs a headless gl context, which is Mesa's software rasterizer on unix
+    #[test]
+    fn test_headless_post_process()
+    {
+        // the scene is cleared to red, and the pass inverts it
+        let output = std::env::temp_dir().join("magmakit_headless_post_process.png").to_str().unwrap().to_string();
+        launch_from_path("tests/headless/postprocess", Some(HeadlessOptions{dims : (16, 16), frames : 2, output : Some(output.clone())})).unwrap();
+        let image = image::open(&output).unwrap().to_rgba();
+        assert_eq!(image.dimensions(), (16, 16));
+        assert!(image.pixels().all(|pixel| pixel.0 == [0, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,10 @@
+#version 330
+in vec2 f_texcoord;
+in vec4 f_color;
+out vec4 color;
+uniform sampler2D tex;
+void main()
+{
+    vec4 source = texture(tex, f_texcoord);
+    color = vec4(1.0 - source.rgb, source.a);
+}
@@ -0,0 +1,16 @@
+#version 330
+in vec2 position;
+in vec4 vertex_color;
+in vec2 vertex_texcoord;
+out vec2 f_texcoord;
+out vec4 f_color;
+uniform mat4 matrix_view;
+uniform mat4 matrix_command;
+uniform vec2 tex_topleft;
+uniform vec2 tex_bottomright;
+void main()
+{
+    f_texcoord = tex_topleft + vertex_texcoord*(tex_bottomright-tex_topleft);
+    f_color = vertex_color;
+    gl_Position = matrix_view * matrix_command * vec4(position, 0.0, 1.0);
+}